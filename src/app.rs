@@ -1,7 +1,34 @@
-use std::collections::HashMap;
-use std::process::{Command, ExitStatus};
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt;
-use regex::Regex;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+
+use crate::ci::{self, CiTarget};
+use crate::config::Config;
+use crate::container;
+use crate::editor;
+use crate::error::AppError;
+use crate::form::Form;
+use crate::licence::{self, Licence};
+use crate::lint;
+use crate::metadata::{self, Metadata};
+use crate::overlay;
+use crate::plan::{Plan, PlannedCommand, PlannedFile};
+use crate::readme::{self, ReadmeOptions, ReadmeSection};
+use crate::recipe::{self, Recipe};
+use crate::scaffold;
+use crate::script::{self, ScriptKind};
+use crate::source;
+use crate::steps::{self, PostCreateStep, StepEvent, StepState, StepStatus};
+use crate::template::{self, Template};
+use crate::toolchain::{self, Availability, Toolchain};
+use crate::update::{ProjectRecord, RECORD_FILE};
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 /// The different screens of the app
@@ -11,6 +38,10 @@ use regex::Regex;
 ///  - SelectProjectType: The screen to select the project type
 ///  - SelectProjectName: The screen to select the project name
 ///  - SelectProjectFolder: The screen to select the project folder
+///  - Metadata: The form asking for the project's description, authors and other details
+///  - TemplatePrompts: The form asking for the variables a template declares
+///  - SelectProjectOptions: The screen to choose the extra options for the project
+///  - SelectFeatures: The checklist of add-ons for the project
 ///  - CreateProject: The screen to create the project
 ///  - ProjectCreated: The screen to show the project created
 pub enum CurrentScreen {
@@ -18,14 +49,19 @@ pub enum CurrentScreen {
     SelectProjectType,
     SelectProjectName,
     SelectProjectFolder,
+    Metadata,
+    TemplatePrompts,
+    SelectProjectOptions,
+    SelectFeatures,
     CreateProject,
     ProjectCreated,
 }
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
 /// The different types of projectse
 pub enum ProjectTypes {
-    /// A python project using the venv package manager
+    /// A python project using the venv package manager, the type the wizard and recipes start with
+    #[default]
     Python,
     /// A python project using the uv package manager
     UvPython,
@@ -33,6 +69,62 @@ pub enum ProjectTypes {
     Rust,
     /// A C++ project using the CMake build system
     CmakeCpp,
+    /// A user template, indexing into `App::templates`
+    Template(usize),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+/// The options that can be changed on the options screen
+pub enum ProjectOption {
+    /// Which CI configuration to generate
+    Ci,
+    /// Which licence to add
+    Licence,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+/// The add-ons that can be ticked on the features screen
+pub enum ProjectFeature {
+    /// A first passing test and the setup to run it
+    Tests,
+    /// The CI config chosen on the options screen
+    Ci,
+    /// A Dockerfile and dev container
+    Container,
+    /// The licence chosen on the options screen
+    Licence,
+    /// A pre-commit config running the formatter and linter
+    PreCommit,
+    /// `.editorconfig` and VS Code settings
+    EditorConfig,
+    /// The formatter's config, like `rustfmt.toml`
+    Formatter,
+    /// A section of the generated README
+    Readme(ReadmeSection),
+    /// A feature template, indexing into `App::templates`
+    Template(usize),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+/// The extra choices made for a project on the options and features screens
+pub struct ProjectOptions {
+    /// Which CI configuration to generate
+    pub ci: CiTarget,
+    /// Which licence to add
+    pub licence: Licence,
+    /// Whether to generate a first test
+    pub tests: bool,
+    /// Whether to generate a Dockerfile and dev container
+    pub container: bool,
+    /// Whether to generate a pre-commit config
+    pub pre_commit: bool,
+    /// Whether to generate `.editorconfig` and VS Code settings
+    pub editor_config: bool,
+    /// Whether to generate the formatter's config
+    pub formatter: bool,
+    /// Which sections to write in the README
+    pub readme: ReadmeOptions,
 }
 
 /// Struct containing important app data
@@ -45,8 +137,52 @@ pub struct App {
     pub project_name: Option<String>,
     /// The folder of the project being created
     pub project_folder: Option<String>,
-    /// The command to be executed (stored as string)
-    pub command: Option<String>,
+    /// The plan of what will be created
+    pub plan: Option<Plan>,
+    /// The templates found in the user's templates folder, including the ones that are features
+    pub templates: Vec<Template>,
+    /// The features chosen to add on top of the project, by name
+    pub features: BTreeSet<String>,
+    /// Extra variables given to templates, from a recipe
+    pub variables: BTreeMap<String, String>,
+    /// The recipe pre-filling the wizard, if one was given
+    pub recipe: Option<Recipe>,
+    /// The last error to show to the user
+    pub error: Option<AppError>,
+    /// A short note about the last action, like opening the editor
+    pub message: Option<String>,
+    /// The folder to start a shell in once the TUI has closed
+    pub shell_dir: Option<PathBuf>,
+    /// The absolute path of the last project created
+    pub created_dir: Option<PathBuf>,
+    /// The user's configuration
+    pub config: Config,
+    /// The external tools found when the app started
+    pub toolchain: Toolchain,
+    /// The post create steps of the last project created
+    pub steps: Vec<StepState>,
+    /// The events from the post create steps while they are running
+    pub step_events: Option<Receiver<StepEvent>>,
+    /// Set to cancel the post create steps that are running
+    pub step_cancel: Arc<AtomicBool>,
+    /// The extra options chosen for the project
+    pub options: ProjectOptions,
+    /// The description, authors and other details of the project
+    pub metadata: Metadata,
+    /// The answers to the metadata form or the template's prompts while either is shown
+    pub form: Option<Form>,
+    /// The option currently highlighted on the options screen
+    pub option_index: usize,
+    /// The add-on currently highlighted on the features screen
+    pub feature_index: usize,
+    /// Whether projects are only previewed and never written
+    pub dry_run: bool,
+    /// The plans confirmed during a dry run, printed when the app exits
+    pub dry_run_plans: Vec<Plan>,
+    /// The entry of the file tree selected on the confirmation screen
+    pub preview_selected: usize,
+    /// How far the file contents on the confirmation screen are scrolled
+    pub preview_scroll: u16,
     /// The text input for the current screen
     pub text_input: String,
     /// The current screen being displayed
@@ -55,112 +191,679 @@ pub struct App {
 
 impl App {
     pub fn new() -> App {
+        let (config, error) = match Config::load() {
+            Ok(config) => (config, None),
+            Err(error) => (Config::default(), Some(error)),
+        };
+        let metadata = Metadata::defaults(&config.metadata);
+
         App {
             current_project: None,
-            project_type: ProjectTypes::Python,
+            project_type: ProjectTypes::default(),
             project_name: None,
             project_folder: None,
-            command: None,
+            plan: None,
+            templates: template::discover().into_iter().chain(source::recent()).collect(),
+            features: BTreeSet::new(),
+            variables: BTreeMap::new(),
+            recipe: None,
+            error,
+            message: None,
+            shell_dir: None,
+            created_dir: None,
+            config,
+            toolchain: Toolchain::probe(),
+            steps: Vec::new(),
+            step_events: None,
+            step_cancel: Arc::new(AtomicBool::new(false)),
+            options: ProjectOptions::default(),
+            metadata,
+            form: None,
+            option_index: 0,
+            feature_index: 0,
+            dry_run: false,
+            dry_run_plans: Vec::new(),
+            preview_selected: 0,
+            preview_scroll: 0,
             text_input: String::new(),
             current_screen: CurrentScreen::Main,
         }
     }
 
-    /// Save the current project's command to the struct
+    /// Save the current project's plan to the struct
     pub fn save_project(&mut self) {
-        if let Some(project_folder) = &self.project_folder {
-            if let Some(project_name) = &self.project_name {
-                #[allow(unused_mut)]
-                let mut command: String;
-                match self.project_type {
-                    ProjectTypes::Python => {
-                        if cfg!(target_os = "windows") {
-                            command = format!(
-                                "New-Item -ItemType Directory -Name {} -Force; Set-Location {}; python3 -m venv .venv; '# {}' | Out-File -FilePath README.md -Encoding UTF8; New-Item -ItemType Directory -Name src -Force; Set-Location src; 'def main():' | Out-File -FilePath main.py -Encoding UTF8; '    print(\"Hello, World!\")' | Add-Content -Path main.py; '' | Add-Content -Path main.py; 'main()' | Add-Content -Path main.py; Set-Location ..; .venv\\Scripts\\Activate.ps1",
-                                project_folder,
-                                project_folder,
-                                project_name
-                            );
-                        } else {
-                            command = format!(
-                                "mkdir {} && cd {} && python3 -m venv .venv && echo # {} > README.md && mkdir src && cd src && echo 'def main():\n  print(\"Hello, World!\")' > main.py && cd .. && source .venv/bin/activate",
-                                project_folder,
-                                project_folder,
-                                project_name
-                            );
-                        }
+        self.plan = None;
+        self.error = None;
+        if let (Some(project_folder), Some(project_name)) = (&self.project_folder, &self.project_name) {
+            let result = match self.project_type {
+                ProjectTypes::Template(index) => {
+                    let mut variables = self.variables.clone();
+                    variables.insert("project_name".to_string(), project_name.clone());
+                    variables.insert("project_folder".to_string(), project_folder.clone());
+                    self.metadata.add_variables(&mut variables);
+                    self.templates[index].plan(Path::new(project_folder), variables).map_err(AppError::from)
+                }
+                project_type => Ok(builtin_plan(project_type, project_name, project_folder, &self.toolchain, &self.metadata, self.options.licence)),
+            };
+
+            match result {
+                Ok(mut plan) => {
+                    plan.post_create = self.post_create_steps();
+                    ci::add_ci(&mut plan, self.project_type, self.options.ci);
+                    licence::add_licence(&mut plan, self.options.licence, project_name, &self.metadata.authors);
+                    if self.options.tests {
+                        scaffold::add_tests(&mut plan, self.project_type, project_name);
+                    }
+                    if self.options.container {
+                        container::add_container(&mut plan, self.project_type, project_name);
+                    }
+                    if self.options.pre_commit {
+                        lint::add_pre_commit(&mut plan, self.project_type);
                     }
-                    ProjectTypes::UvPython => {
-                        command = format!("uv init --name {} --vcs git --app --no-description --author-from git {}", project_name, project_folder);
+                    if self.options.editor_config {
+                        editor::add_editor_config(&mut plan, self.project_type, project_name);
                     }
-                    ProjectTypes::Rust => {
-                        command = format!("cargo new --name {} --vcs git --bin --edition 2024 {}", project_name, project_folder);
+                    if self.options.formatter {
+                        lint::add_formatter(&mut plan, self.project_type);
                     }
-                    ProjectTypes::CmakeCpp => {
-                        if cfg!(target_os = "windows") {
-                            command = format!(
-                                "New-Item -ItemType Directory -Name {} -Force; Set-Location {}; '# {}' | Out-File -FilePath README.md -Encoding UTF8; New-Item -ItemType Directory -Name src -Force; Set-Location src; '#include <iostream>' | Out-File -FilePath main.cpp -Encoding UTF8; 'int main() {{' | Add-Content -Path main.cpp; '    std::cout << \"Hello, World!\" << std::endl;' | Add-Content -Path main.cpp; '    return 0;' | Add-Content -Path main.cpp; '}}' | Add-Content -Path main.cpp; Set-Location ..; 'cmake_minimum_required(VERSION 3.10)' | Out-File -FilePath CMakeLists.txt -Encoding UTF8; 'project({} CXX)' | Add-Content -Path CMakeLists.txt; 'set(CMAKE_CXX_STANDARD 17)' | Add-Content -Path CMakeLists.txt; 'set(CMAKE_CXX_STANDARD_REQUIRED ON)' | Add-Content -Path CMakeLists.txt; 'if(WIN32)' | Add-Content -Path CMakeLists.txt; '    set(CMAKE_CXX_FLAGS \"${{CMAKE_CXX_FLAGS}} -fexceptions\")' | Add-Content -Path CMakeLists.txt; '    set(CMAKE_C_FLAGS \"${{CMAKE_C_FLAGS}} -fexceptions\")' | Add-Content -Path CMakeLists.txt; 'endif()' | Add-Content -Path CMakeLists.txt; 'add_executable(${{PROJECT_NAME}} src/main.cpp)' | Add-Content -Path CMakeLists.txt",
-                                project_folder,
-                                project_folder,
-                                project_name,
-                                project_name
-                            );
-                        } else {
-                            command = format!(
-                                "mkdir {} && cd {} && echo # {} > README.md && mkdir src && cd src && echo '#include <iostream>' > main.cpp && echo 'int main() {{' >> main.cpp && echo '    std::cout << \"Hello, World!\" << std::endl;' >> main.cpp && echo '    return 0;' >> main.cpp && echo '}}' >> main.cpp && cd .. && echo 'cmake_minimum_required(VERSION 3.10)' > CMakeLists.txt && echo 'project({})' >> CMakeLists.txt && echo 'set(CMAKE_CXX_STANDARD 17)' >> CMakeLists.txt && echo 'set(CMAKE_CXX_STANDARD_REQUIRED ON)' >> CMakeLists.txt && echo 'add_executable(${{PROJECT_NAME}} src/main.cpp)' >> CMakeLists.txt",
-                                project_folder,
-                                project_folder,
-                                project_name,
-                                project_name
-                            );
+                    let options = self.options;
+                    let result = readme::add_readme(&mut plan, self.project_type, &self.metadata, options.ci, options.licence, &options.readme)
+                        .and_then(|()| overlay::apply(&mut plan, &self.templates, &self.features, &self.base_names(), &self.config.post_create));
+                    match result {
+                        Ok(()) => {
+                            let record = ProjectRecord { files: ProjectRecord::fingerprint_files(&plan), ..self.to_record() };
+                            plan.add_file(RECORD_FILE, record.to_toml());
+                            self.plan = Some(plan);
                         }
+                        Err(error) => self.error = Some(error),
                     }
-                };
+                }
+                Err(error) => self.error = Some(error),
+            }
+        }
+    }
 
-                // Store the command string
-                self.command = Some(command);
+    /// Create the project from the plan in the struct
+    pub fn create_project(&self) -> Result<(), AppError> {
+        let plan = self.plan.as_ref().ok_or_else(|| AppError::Validation("there is no project to create".to_string()))?;
+        plan.apply().map_err(|e| e.context(format!("Couldn't create the project in {}", plan.root.display())))
+    }
+
+    /// Get the post create steps configured for the current project type
+    pub fn post_create_steps(&self) -> Vec<PostCreateStep> {
+        let post_create = &self.config.post_create;
+        match self.project_type {
+            ProjectTypes::Python => post_create.python.clone(),
+            ProjectTypes::UvPython => post_create.uv_python.clone(),
+            ProjectTypes::Rust => post_create.rust.clone(),
+            ProjectTypes::CmakeCpp => post_create.cmake_cpp.clone(),
+            ProjectTypes::Template(index) => post_create.for_template(&self.templates[index]),
+        }
+    }
+
+    /// Start running the post create steps in the new project
+    pub fn start_post_create(&mut self) {
+        let Some(plan) = &self.plan else {
+            return;
+        };
+        self.steps = plan
+            .post_create
+            .iter()
+            .map(|step| StepState { step: step.clone(), status: StepStatus::Pending, output: Vec::new() })
+            .collect();
+
+        if !plan.post_create.is_empty() {
+            self.step_cancel = Arc::new(AtomicBool::new(false));
+            self.step_events = Some(steps::spawn(plan.post_create.clone(), &plan.root, Arc::clone(&self.step_cancel)));
+        }
+    }
+
+    /// Kill the running post create step and skip the rest
+    pub fn cancel_steps(&mut self) {
+        if self.steps_running() {
+            self.step_cancel.store(true, Ordering::Relaxed);
+            self.message = Some("Cancelled the post create steps".to_string());
+        }
+    }
+
+    /// Cancel the post create steps and wait for them to stop, so none are left running when the app exits
+    pub fn stop_steps(&mut self) {
+        self.cancel_steps();
+        while self.steps_running() {
+            self.poll_steps();
+            thread::sleep(Duration::from_millis(20));
+        }
+    }
+
+    /// Apply every event the post create steps have sent since the last call
+    pub fn poll_steps(&mut self) {
+        let Some(events) = &self.step_events else {
+            return;
+        };
+
+        loop {
+            match events.try_recv() {
+                Ok(StepEvent::Started(index)) => self.steps[index].status = StepStatus::Running,
+                Ok(StepEvent::Output(index, line)) => self.steps[index].output.push(line),
+                Ok(StepEvent::Finished(index, Ok(()))) => self.steps[index].status = StepStatus::Succeeded,
+                Ok(StepEvent::Finished(index, Err(error))) => self.steps[index].status = StepStatus::Failed(error),
+                Ok(StepEvent::Skipped(index)) => self.steps[index].status = StepStatus::Skipped,
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.step_events = None;
+                    break;
+                }
             }
         }
     }
 
-    /// Create the project from the command in the struct
-    pub fn create_project(&self) -> i32 {
-        if let Some(command) = &self.command {
-            let command_status = if cfg!(target_os = "windows") {
-                Command::new("powershell")
-                    .args(&["-Command", command])
-                    .stdout(std::process::Stdio::null())
-                    .stderr(std::process::Stdio::null())
-                    .status()
-            } else {
-                Command::new("bash")
-                    .args(&["-c", command])
-                    .stdout(std::process::Stdio::null())
-                    .stderr(std::process::Stdio::null())
-                    .status()
-            };
+    /// Whether the post create steps are still running
+    pub fn steps_running(&self) -> bool {
+        self.step_events.is_some()
+    }
 
-            match command_status {
-                Ok(status) => {
-                    if status.success() {
-                        0
-                    } else {
-                        status.code().unwrap_or(-1)
-                    }
+    /// Move on from the folder screen to the metadata form
+    pub fn start_metadata(&mut self) {
+        self.form = Some(Form::new(Metadata::prompts(), self.metadata.to_answers()));
+        self.current_screen = CurrentScreen::Metadata;
+    }
+
+    /// Move on from the metadata form once every answer is valid, otherwise focus the first bad one
+    pub fn finish_metadata(&mut self) {
+        let Some(form) = &mut self.form else {
+            return;
+        };
+        if let Some(index) = form.first_error() {
+            form.focus = index;
+            return;
+        }
+        self.metadata = Metadata::from_answers(&form.variables);
+        self.form = None;
+        self.start_prompts();
+    }
+
+    /// Show the template's form if it has prompts, or else the options
+    pub fn start_prompts(&mut self) {
+        let ProjectTypes::Template(index) = self.project_type else {
+            self.start_options();
+            return;
+        };
+        let prompts = self.templates[index].manifest.prompts.clone();
+        if prompts.is_empty() {
+            self.start_options();
+            return;
+        }
+
+        let mut variables = self.variables.clone();
+        if let (Some(name), Some(folder)) = (&self.project_name, &self.project_folder) {
+            variables.insert("project_name".to_string(), name.clone());
+            variables.insert("project_folder".to_string(), folder.clone());
+        }
+        template::add_name_variants(&mut variables);
+        self.metadata.add_variables(&mut variables);
+        self.form = Some(Form::new(prompts, variables));
+        self.current_screen = CurrentScreen::TemplatePrompts;
+    }
+
+    /// Move on from the form once every answer is valid, otherwise focus the first bad one
+    pub fn finish_form(&mut self) {
+        let Some(form) = &mut self.form else {
+            return;
+        };
+        if let Some(index) = form.first_error() {
+            form.focus = index;
+            return;
+        }
+        for prompt in &form.prompts {
+            if let Some(value) = form.variables.get(&prompt.name) {
+                self.variables.insert(prompt.name.clone(), value.clone());
+            }
+        }
+        self.form = None;
+        self.start_options();
+    }
+
+    /// Show the options screen, turning off any option the project type doesn't support
+    pub fn start_options(&mut self) {
+        self.option_index = 0;
+        if !ci::supports(self.project_type) {
+            self.options.ci = CiTarget::None;
+        }
+        self.current_screen = CurrentScreen::SelectProjectOptions;
+    }
+
+    /// Get the options that apply to the current project type
+    pub fn available_options(&self) -> Vec<ProjectOption> {
+        let mut options = Vec::new();
+        if ci::supports(self.project_type) {
+            options.push(ProjectOption::Ci);
+        }
+        options.push(ProjectOption::Licence);
+        options
+    }
+
+    /// Move the highlighted option up or down, wrapping around the ends
+    pub fn move_option(&mut self, up: bool) {
+        let count = self.available_options().len();
+        if count == 0 {
+            return;
+        }
+        self.option_index = if up {
+            (self.option_index + count - 1) % count
+        } else {
+            (self.option_index + 1) % count
+        };
+    }
+
+    /// Change the value of the highlighted option
+    pub fn change_option(&mut self, forward: bool) {
+        match self.available_options().get(self.option_index) {
+            Some(ProjectOption::Ci) => {
+                self.options.ci = if forward { self.options.ci.next() } else { self.options.ci.previous() };
+            }
+            Some(ProjectOption::Licence) => {
+                self.options.licence = if forward { self.options.licence.next() } else { self.options.licence.previous() };
+            }
+            None => {}
+        }
+    }
+
+    /// Get the label and current value of an option
+    pub fn option_value(&self, option: ProjectOption) -> (String, String) {
+        match option {
+            ProjectOption::Ci => ("CI".to_string(), self.options.ci.to_string()),
+            ProjectOption::Licence => ("Licence".to_string(), self.options.licence.to_string()),
+        }
+    }
+
+    /// Show the features screen, unticking anything the project type can't have
+    pub fn start_features(&mut self) {
+        self.feature_index = 0;
+        for feature in self.feature_list() {
+            if self.feature_unavailable(feature).is_some() {
+                self.set_feature(feature, false);
+            }
+        }
+        self.current_screen = CurrentScreen::SelectFeatures;
+    }
+
+    /// Get every add-on in the order they are listed, including the ones the project type can't have
+    pub fn feature_list(&self) -> Vec<ProjectFeature> {
+        let mut features = vec![
+            ProjectFeature::Tests,
+            ProjectFeature::Ci,
+            ProjectFeature::Container,
+            ProjectFeature::Licence,
+            ProjectFeature::PreCommit,
+            ProjectFeature::EditorConfig,
+            ProjectFeature::Formatter,
+        ];
+        features.extend(ReadmeSection::ALL.map(ProjectFeature::Readme));
+        let templates = (0..self.templates.len()).filter(|index| self.templates[*index].manifest.overlay.is_some());
+        features.extend(templates.map(ProjectFeature::Template));
+        features
+    }
+
+    /// Get why an add-on can't be ticked for the current project, if it can't
+    pub fn feature_unavailable(&self, feature: ProjectFeature) -> Option<String> {
+        let supported = match feature {
+            ProjectFeature::Tests => scaffold::supports(self.project_type),
+            ProjectFeature::Ci => ci::supports(self.project_type),
+            ProjectFeature::Licence => true,
+            ProjectFeature::Container => container::supports(self.project_type),
+            ProjectFeature::PreCommit => true,
+            ProjectFeature::EditorConfig => editor::supports(self.project_type),
+            ProjectFeature::Formatter => lint::supports_formatter(self.project_type),
+            ProjectFeature::Readme(_) => readme::supports(self.project_type),
+            ProjectFeature::Template(index) => {
+                let template = &self.templates[index];
+                let manifest = template.manifest.overlay.as_ref()?;
+                if !overlay::applies(manifest, &self.base_names()) {
+                    return Some(format!("only for {}", manifest.applies_to.join(", ")));
                 }
-                Err(_) => {
-                    -1
+                let id = template.id();
+                let conflict = self.features.iter().find(|chosen| {
+                    manifest.conflicts.contains(chosen)
+                        || self.templates.iter().any(|other| {
+                            other.id() == **chosen && other.manifest.overlay.as_ref().is_some_and(|other| other.conflicts.contains(&id))
+                        })
+                });
+                return conflict.map(|other| format!("can't be used with {}", other));
+            }
+        };
+        (!supported).then(|| format!("not available for {}", self.project_type_name(self.project_type)))
+    }
+
+    /// Whether an add-on is ticked
+    pub fn feature_checked(&self, feature: ProjectFeature) -> bool {
+        match feature {
+            ProjectFeature::Tests => self.options.tests,
+            ProjectFeature::Ci => self.options.ci != CiTarget::None,
+            ProjectFeature::Licence => self.options.licence != Licence::None,
+            ProjectFeature::Container => self.options.container,
+            ProjectFeature::PreCommit => self.options.pre_commit,
+            ProjectFeature::EditorConfig => self.options.editor_config,
+            ProjectFeature::Formatter => self.options.formatter,
+            ProjectFeature::Readme(section) => section.get(&self.options.readme),
+            ProjectFeature::Template(index) => self.features.contains(&self.templates[index].id()),
+        }
+    }
+
+    /// Get the name and description of an add-on
+    pub fn feature_info(&self, feature: ProjectFeature) -> (String, String) {
+        let (name, description) = match feature {
+            ProjectFeature::Tests => ("Tests", "A first passing test and what is needed to run it"),
+            ProjectFeature::Ci => {
+                let name = match self.options.ci {
+                    CiTarget::None => "CI".to_string(),
+                    ci => format!("CI ({})", ci),
+                };
+                return (name, "The CI config chosen on the options screen, go back to choose another".to_string());
+            }
+            ProjectFeature::Licence => {
+                let name = match self.options.licence {
+                    Licence::None => "Licence".to_string(),
+                    licence => format!("Licence ({})", licence),
+                };
+                return (name, "A LICENSE file with the licence chosen on the options screen, go back to choose another".to_string());
+            }
+            ProjectFeature::Container => ("Docker + Dev Container", "A Dockerfile, .dockerignore and a VS Code dev container"),
+            ProjectFeature::PreCommit => ("Pre-commit", "A .pre-commit-config.yaml running the formatter and linter before each commit"),
+            ProjectFeature::EditorConfig => ("Editor Config", "An .editorconfig and VS Code settings and launch config"),
+            ProjectFeature::Formatter => ("Formatter Config", "The config for rustfmt, ruff or clang-format"),
+            ProjectFeature::Readme(section) => section.info(),
+            ProjectFeature::Template(index) => {
+                let manifest = &self.templates[index].manifest;
+                return (manifest.name.clone(), manifest.description.clone());
+            }
+        };
+        (name.to_string(), description.to_string())
+    }
+
+    /// Tick or untick an add-on
+    fn set_feature(&mut self, feature: ProjectFeature, checked: bool) {
+        match feature {
+            ProjectFeature::Tests => self.options.tests = checked,
+            // Which CI and licence is chosen on the options screen, ticking one here picks the first
+            // and ticking one that already is keeps the choice
+            ProjectFeature::Ci if checked != self.feature_checked(feature) => {
+                self.options.ci = if checked { CiTarget::None.next() } else { CiTarget::None };
+            }
+            ProjectFeature::Licence if checked != self.feature_checked(feature) => {
+                self.options.licence = if checked { Licence::None.next() } else { Licence::None };
+            }
+            ProjectFeature::Ci | ProjectFeature::Licence => {}
+            ProjectFeature::Container => self.options.container = checked,
+            ProjectFeature::PreCommit => self.options.pre_commit = checked,
+            ProjectFeature::EditorConfig => self.options.editor_config = checked,
+            ProjectFeature::Formatter => self.options.formatter = checked,
+            ProjectFeature::Readme(section) => section.set(&mut self.options.readme, checked),
+            ProjectFeature::Template(index) => {
+                let id = self.templates[index].id();
+                if checked {
+                    self.features.insert(id);
+                } else {
+                    self.features.remove(&id);
                 }
             }
+        }
+    }
+
+    /// Move the highlighted add-on up or down, wrapping around the ends
+    pub fn move_feature(&mut self, up: bool) {
+        let count = self.feature_list().len();
+        if count == 0 {
+            return;
+        }
+        self.feature_index = if up {
+            (self.feature_index + count - 1) % count
+        } else {
+            (self.feature_index + 1) % count
+        };
+    }
+
+    /// Tick or untick the highlighted add-on, if the project can have it
+    pub fn toggle_feature(&mut self) {
+        let Some(feature) = self.feature_list().get(self.feature_index).copied() else {
+            return;
+        };
+        let checked = self.feature_checked(feature);
+        if checked || self.feature_unavailable(feature).is_none() {
+            self.set_feature(feature, !checked);
+        }
+    }
+
+    /// Tick every add-on the project can have, or untick them all if they already are
+    ///
+    /// Features that conflict with one ticked earlier in the list are left unticked.
+    pub fn toggle_all_features(&mut self) {
+        let features = self.feature_list();
+        let all = features.iter().all(|feature| self.feature_checked(*feature) || self.feature_unavailable(*feature).is_some());
+        for feature in features {
+            if all {
+                self.set_feature(feature, false);
+            } else if self.feature_unavailable(feature).is_none() {
+                self.set_feature(feature, true);
+            }
+        }
+    }
+
+    /// Get the names the current project goes by when matching a feature's `applies_to`
+    fn base_names(&self) -> Vec<String> {
+        match self.project_type {
+            ProjectTypes::Template(index) => vec![self.templates[index].id(), self.templates[index].manifest.name.clone()],
+            project_type => recipe::type_key(project_type).map(str::to_string).into_iter().collect(),
+        }
+    }
+
+    /// Move the selection in the file tree of the preview
+    pub fn move_preview(&mut self, up: bool) {
+        let count = self.plan.as_ref().map(|plan| plan.tree().len()).unwrap_or(0);
+        if count == 0 {
+            return;
+        }
+        self.preview_selected = if up {
+            (self.preview_selected + count - 1) % count
         } else {
-            -1
+            (self.preview_selected + 1) % count
+        };
+        self.preview_scroll = 0;
+    }
+
+    /// Get the file selected in the preview, if a file rather than a directory is selected
+    pub fn preview_file(&self) -> Option<&PlannedFile> {
+        let plan = self.plan.as_ref()?;
+        let entry = plan.tree().into_iter().nth(self.preview_selected)?;
+        plan.files.get(entry.file?)
+    }
+
+    /// Save the plan as a script in the current folder that creates the same project
+    pub fn save_script(&mut self) {
+        let (Some(plan), None) = (&self.plan, &self.error) else {
+            return;
+        };
+        let kind = ScriptKind::native();
+        let folder = plan.root.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_else(|| "project".to_string());
+        let path = PathBuf::from(format!("create-{}.{}", folder, kind.extension()));
+
+        let result = fs::write(&path, script::emit(plan, kind));
+        #[cfg(unix)]
+        let result = result.and_then(|_| {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755))
+        });
+        self.message = Some(match result {
+            Ok(()) => format!("Saved the script to {}", path.display()),
+            Err(e) => format!("Couldn't save the script to {}: {}", path.display(), e),
+        });
+    }
+
+    /// Take the choices from a recipe, which pre-fill the wizard or create a project directly
+    pub fn apply_recipe(&mut self, recipe: &Recipe) -> Result<(), AppError> {
+        self.project_type = match (&recipe.template, &recipe.project_type) {
+            (Some(_), Some(_)) => return Err(AppError::Validation("a recipe can't have both `type` and `template`".to_string())),
+            // Anything that looks like a repository is fetched again so it is up to date
+            (Some(name), None) if name.contains("://") || name.contains('#') || Path::new(name).exists() => self.add_git_template(name)?,
+            (Some(name), None) => self
+                .templates
+                .iter()
+                .position(|template| template.manifest.name == *name && template.manifest.overlay.is_none())
+                .map(ProjectTypes::Template)
+                .ok_or_else(|| AppError::Validation(format!("there is no template named `{}`", name)))?,
+            (None, Some(key)) => recipe::type_from_key(key).ok_or_else(|| AppError::Validation(format!("unknown project type `{}`", key)))?,
+            // Not the type already chosen, a batch plans every project with the same app
+            (None, None) => ProjectTypes::default(),
+        };
+        self.project_name = recipe.name.as_deref().map(sanitise);
+        self.project_folder = recipe.folder.as_deref().or(recipe.name.as_deref()).map(sanitise);
+        self.options = recipe.options;
+        self.metadata = recipe.metadata.clone().or(&Metadata::defaults(&self.config.metadata));
+        self.metadata.validate().map_err(AppError::Validation)?;
+        self.variables = recipe.variables.clone();
+        self.features = recipe.features.iter().cloned().collect();
+        self.recipe = Some(recipe.clone());
+        Ok(())
+    }
+
+    /// Plan the project a recipe describes, without the wizard
+    pub fn plan_recipe(&mut self, recipe: &Recipe) -> Result<Plan, AppError> {
+        self.apply_recipe(recipe)?;
+        self.plan_choices()
+    }
+
+    /// Plan the project from the choices already made, like by `apply_recipe`
+    pub fn plan_choices(&mut self) -> Result<Plan, AppError> {
+        if self.project_name.is_none() {
+            return Err(AppError::Validation("the recipe needs a `name`".to_string()));
         }
+        self.save_project();
+        if let Some(error) = self.error.take() {
+            return Err(error);
+        }
+        self.plan.clone().ok_or_else(|| AppError::Validation("the recipe doesn't describe a project".to_string()))
+    }
+
+    /// Fetch a template from a git repository and select it
+    ///
+    /// A template that is already in the list is replaced with the newly fetched one.
+    pub fn add_git_template(&mut self, spec: &str) -> Result<ProjectTypes, AppError> {
+        let template = source::fetch(spec)?;
+        let index = match self.templates.iter().position(|existing| existing.root == template.root) {
+            Some(index) => {
+                self.templates[index] = template;
+                index
+            }
+            None => {
+                self.templates.push(template);
+                self.templates.len() - 1
+            }
+        };
+        self.project_type = ProjectTypes::Template(index);
+        Ok(self.project_type)
+    }
+
+    /// Get the choices made for the current project as a recipe
+    pub fn to_recipe(&self) -> Recipe {
+        let (project_type, template) = match self.project_type {
+            ProjectTypes::Template(index) => {
+                let template = &self.templates[index];
+                (None, Some(template.source.clone().unwrap_or_else(|| template.manifest.name.clone())))
+            }
+            project_type => (recipe::type_key(project_type).map(str::to_string), None),
+        };
+        Recipe {
+            project_type,
+            template,
+            name: self.project_name.clone(),
+            folder: self.project_folder.clone(),
+            options: self.options,
+            metadata: self.metadata.clone(),
+            features: self.features.iter().cloned().collect(),
+            variables: self.variables.clone(),
+        }
+    }
+
+    /// Get the record written into the project, so it can be updated from its template later
+    pub fn to_record(&self) -> ProjectRecord {
+        let template = match self.project_type {
+            ProjectTypes::Template(index) => Some(&self.templates[index]),
+            _ => None,
+        };
+        ProjectRecord {
+            generator: env!("CARGO_PKG_VERSION").to_string(),
+            version: template.and_then(|template| template.manifest.version.clone()),
+            commit: template.and_then(|template| template.commit.clone()),
+            recipe: self.to_recipe(),
+            files: BTreeMap::new(),
+        }
+    }
+
+    /// Save the choices made for the current project as a recipe in the current folder
+    pub fn save_recipe(&mut self) {
+        let folder = self.project_folder.clone().unwrap_or_else(|| "project".to_string());
+        let path = PathBuf::from(format!("{}.recipe.toml", folder));
+        self.message = Some(match self.to_recipe().save(&path) {
+            Ok(()) => format!("Saved the recipe to {}", path.display()),
+            Err(e) => format!("Couldn't save the recipe: {}", e),
+        });
+    }
+
+    /// Fill the text input with the recipe's answer for the current screen, if there is one
+    pub fn prefill_input(&mut self) {
+        let Some(recipe) = &self.recipe else {
+            return;
+        };
+        let value = match self.current_screen {
+            CurrentScreen::SelectProjectName => recipe.name.as_deref(),
+            CurrentScreen::SelectProjectFolder => recipe.folder.as_deref().or(recipe.name.as_deref()),
+            _ => None,
+        };
+        if let Some(value) = value {
+            self.text_input = sanitise(value);
+        }
+    }
+
+    /// Get every project type that can be selected, in display order
+    pub fn project_types(&self) -> Vec<ProjectTypes> {
+        let mut types = vec![
+            ProjectTypes::Python,
+            ProjectTypes::UvPython,
+            ProjectTypes::Rust,
+            ProjectTypes::CmakeCpp,
+        ];
+        let templates = (0..self.templates.len()).filter(|index| self.templates[*index].manifest.overlay.is_none());
+        types.extend(templates.map(ProjectTypes::Template));
+        types
+    }
+
+    /// Whether the current project type can be created with the tools that were found
+    pub fn can_create(&self) -> bool {
+        !matches!(self.toolchain.availability(self.project_type), Availability::Missing(_))
+    }
+
+    /// Get the name shown for a project type
+    pub fn project_type_name(&self, project_type: ProjectTypes) -> String {
+        match project_type {
+            ProjectTypes::Template(index) => self.templates[index].manifest.name.clone(),
+            project_type => project_type.to_string(),
+        }
+    }
+
+    /// Move the project type selection up or down, wrapping around the ends
+    pub fn move_project_type(&mut self, up: bool) {
+        let types = self.project_types();
+        let current = types.iter().position(|t| *t == self.project_type).unwrap_or(0);
+        let next = if up {
+            (current + types.len() - 1) % types.len()
+        } else {
+            (current + 1) % types.len()
+        };
+        self.project_type = types[next];
     }
 
     /// Sanitise the current user input to prevent arbitrary command execution
     pub fn sanitise_input(&mut self) {
-        let re = Regex::new(r"[^\w\-]").unwrap();
-        self.text_input = re.replace_all(&self.text_input, "_").to_string();
+        self.text_input = sanitise(&self.text_input);
     }
 }
 
@@ -171,6 +874,99 @@ impl fmt::Display for ProjectTypes {
             ProjectTypes::UvPython => write!(f, "Python with UV"),
             ProjectTypes::Rust => write!(f, "Rust"),
             ProjectTypes::CmakeCpp => write!(f, "C++ with CMake"),
+            ProjectTypes::Template(_) => write!(f, "Template"),
+        }
+    }
+}
+
+/// Replace everything but word characters and `-` so the text is safe to use as a name or folder
+fn sanitise(text: &str) -> String {
+    text.chars().map(|c| if c.is_alphanumeric() || c == '_' || c == '-' { c } else { '_' }).collect()
+}
+
+/// Show a boolean option as a checkbox
+pub fn checkbox(checked: bool) -> String {
+    if checked { "[x]".to_string() } else { "[ ]".to_string() }
+}
+
+/// Build the plan for one of the built in project types
+/// ### Parameters
+///  - project_type: The type of project, must not be a template
+///  - project_name: The sanitised name of the project
+///  - project_folder: The sanitised folder to create the project in
+///  - toolchain: The tools that were found, used to pick fallbacks for missing ones
+///  - metadata: The details written into the project's manifest
+///  - licence: The licence named in the project's manifest
+fn builtin_plan(
+    project_type: ProjectTypes,
+    project_name: &str,
+    project_folder: &str,
+    toolchain: &Toolchain,
+    metadata: &Metadata,
+    licence: Licence,
+) -> Plan {
+    let mut plan = Plan::new(project_folder);
+    plan.variables.insert("project_name".to_string(), project_name.to_string());
+    plan.variables.insert("project_folder".to_string(), project_folder.to_string());
+    metadata.add_variables(&mut plan.variables);
+
+    match project_type {
+        ProjectTypes::Python => {
+            plan.add_command(PlannedCommand::new(toolchain::program_name("python3"), &["-m", "venv", ".venv"]));
+            plan.add_file(
+                "src/main.py",
+                "def main():\n    print(\"Hello, World!\")\n\n\nif __name__ == \"__main__\":\n    main()\n",
+            );
+        }
+        ProjectTypes::UvPython if toolchain.has("uv") => {
+            let vcs = if toolchain.has("git") { "git" } else { "none" };
+            let mut args = vec!["init", "--name", project_name, "--vcs", vcs, "--app", "--author-from", "none"];
+            if !metadata.description.is_empty() {
+                args.extend(["--description", metadata.description.as_str()]);
+            }
+            plan.add_command(PlannedCommand::new("uv", &args));
+            // uv can't be given the rest of the metadata, so its manifest is replaced once it has run
+            let requires_python = toolchain.python_version().unwrap_or_else(|| "3.12".to_string());
+            plan.add_file("pyproject.toml", metadata::pyproject_toml(project_name, metadata, licence, &requires_python));
         }
+        ProjectTypes::UvPython => {
+            // Write what `uv init --app` would so the project works once uv is installed
+            let requires_python = toolchain.python_version().unwrap_or_else(|| "3.12".to_string());
+            plan.add_file("pyproject.toml", metadata::pyproject_toml(project_name, metadata, licence, &requires_python));
+            plan.add_file(".python-version", format!("{}\n", requires_python));
+            plan.add_file(
+                "main.py",
+                format!("def main():\n    print(\"Hello from {}!\")\n\n\nif __name__ == \"__main__\":\n    main()\n", project_name),
+            );
+            plan.add_file(".gitignore", "# Python-generated files\n__pycache__/\n*.py[oc]\nbuild/\ndist/\nwheels/\n*.egg-info\n\n# Virtual environments\n.venv\n");
+            if toolchain.has("git") {
+                plan.add_post_command(PlannedCommand::new("git", &["init", "--quiet"]));
+            }
+        }
+        ProjectTypes::Rust => {
+            plan.add_command(PlannedCommand::new(
+                "cargo",
+                &["init", "--name", project_name, "--vcs", "git", "--bin", "--edition", "2024"],
+            ));
+            // cargo can't be given the metadata, so its manifest is replaced once it has run
+            plan.add_file("Cargo.toml", metadata::cargo_toml(project_name, metadata, licence));
+        }
+        ProjectTypes::CmakeCpp => {
+            let (minimum_version, project) = metadata::cmake_project(project_name, metadata);
+            plan.add_file(
+                "src/main.cpp",
+                "#include <iostream>\n\nint main() {\n    std::cout << \"Hello, World!\" << std::endl;\n    return 0;\n}\n",
+            );
+            plan.add_file(
+                "CMakeLists.txt",
+                format!(
+                    "cmake_minimum_required(VERSION {})\n{}\nset(CMAKE_CXX_STANDARD 17)\nset(CMAKE_CXX_STANDARD_REQUIRED ON)\nif(WIN32)\n    set(CMAKE_CXX_FLAGS \"${{CMAKE_CXX_FLAGS}} -fexceptions\")\n    set(CMAKE_C_FLAGS \"${{CMAKE_C_FLAGS}} -fexceptions\")\nendif()\nadd_executable(${{PROJECT_NAME}} src/main.cpp)\n",
+                    minimum_version, project
+                ),
+            );
+        }
+        ProjectTypes::Template(_) => {}
     }
+
+    plan
 }
@@ -0,0 +1,174 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use serde::Deserialize;
+
+use crate::error::AppError;
+use crate::plan::{display_command, Plan};
+use crate::recipe::Recipe;
+use crate::steps::{self, StepEvent};
+
+/// A file describing many projects to create in one run
+///
+/// Each `[[project]]` is a recipe, with anything it leaves out taken from
+/// `[defaults]`. `names` is a short way to add projects that only differ in name.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Manifest {
+    /// How many projects to create at once
+    pub jobs: Option<usize>,
+    /// The recipe every project starts from
+    pub defaults: toml::Table,
+    /// Projects that only set a name
+    pub names: Vec<String>,
+    /// Projects that set more than a name
+    #[serde(rename = "project")]
+    pub projects: Vec<toml::Table>,
+}
+
+impl Manifest {
+    /// Read a manifest from a TOML file
+    pub fn load(path: &Path) -> Result<Manifest, AppError> {
+        let text = fs::read_to_string(path).map_err(AppError::io(path))?;
+        toml::from_str(&text).map_err(|e| AppError::ConfigParse(path.to_path_buf(), e.message().to_string()))
+    }
+
+    /// Get the recipe of every project with the defaults filled in, in the order they were listed
+    ///
+    /// A project whose recipe doesn't make sense gets an error instead, so the rest can still be created.
+    pub fn recipes(&self) -> Vec<(String, Result<Recipe, String>)> {
+        let named = self.names.iter().map(|name| {
+            let mut table = toml::Table::new();
+            table.insert("name".to_string(), toml::Value::String(name.clone()));
+            table
+        });
+
+        named
+            .chain(self.projects.iter().cloned())
+            .enumerate()
+            .map(|(index, project)| {
+                let mut table = self.defaults.clone();
+                merge(&mut table, project);
+                let label = match table.get("name") {
+                    Some(toml::Value::String(name)) => name.clone(),
+                    _ => format!("project {}", index + 1),
+                };
+                let recipe = Recipe::deserialize(toml::Value::Table(table)).map_err(|e| e.message().to_string());
+                (label, recipe)
+            })
+            .collect()
+    }
+}
+
+/// Merge one TOML table into another, with tables merged key by key and anything else replaced
+fn merge(base: &mut toml::Table, overlay: toml::Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(overlay)) => merge(base, overlay),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// How far one project in a batch has got
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum JobStatus {
+    Waiting,
+    Running,
+    Succeeded,
+    Failed(String),
+    /// The batch was stopped before this project was started
+    Cancelled,
+}
+
+/// One project in a batch
+#[derive(Clone, Debug)]
+pub struct Job {
+    /// The name shown for the project
+    pub name: String,
+    /// The folder the project is created in
+    pub folder: String,
+    pub status: JobStatus,
+    /// The last line printed while creating the project
+    pub output: Option<String>,
+}
+
+/// A message from the worker threads about one of the projects
+pub enum BatchEvent {
+    /// A project has been started
+    Started(usize),
+    /// A line of output from one of a project's commands
+    Output(usize, String),
+    /// A project has finished, with an error if it failed
+    Finished(usize, Result<(), String>),
+}
+
+/// Create the projects on background threads, at most `jobs` at a time
+///
+/// Setting `cancel` stops projects that haven't started yet from being started.
+/// ### Returns
+///  - The events for every project, the channel closes once every worker has stopped
+pub fn spawn(plans: Vec<(usize, Plan)>, jobs: usize, cancel: Arc<AtomicBool>) -> Receiver<BatchEvent> {
+    let (sender, receiver) = mpsc::channel();
+    let queue = Arc::new(Mutex::new(VecDeque::from(plans)));
+
+    for _ in 0..jobs.max(1) {
+        let queue = Arc::clone(&queue);
+        let cancel = Arc::clone(&cancel);
+        let sender = sender.clone();
+        thread::spawn(move || {
+            while !cancel.load(Ordering::Relaxed) {
+                let Some((index, plan)) = queue.lock().ok().and_then(|mut queue| queue.pop_front()) else {
+                    break;
+                };
+                let _ = sender.send(BatchEvent::Started(index));
+                let result = create(index, &plan, &sender);
+                let _ = sender.send(BatchEvent::Finished(index, result));
+            }
+        });
+    }
+
+    receiver
+}
+
+/// Create one project and run its post create steps, forwarding their output
+fn create(index: usize, plan: &Plan, sender: &Sender<BatchEvent>) -> Result<(), String> {
+    plan.apply().map_err(|e| e.to_string())?;
+
+    let mut failed = None;
+    // Stopping a batch lets the projects already started finish, so their steps are never cancelled
+    for event in steps::spawn(plan.post_create.clone(), &plan.root, Arc::new(AtomicBool::new(false))) {
+        match event {
+            StepEvent::Started(step) => {
+                let _ = sender.send(BatchEvent::Output(index, format!("$ {}", display_command(&plan.post_create[step].command))));
+            }
+            StepEvent::Output(_, line) => {
+                let _ = sender.send(BatchEvent::Output(index, line));
+            }
+            StepEvent::Finished(step, Err(error)) if plan.post_create[step].required => failed = Some(error),
+            _ => {}
+        }
+    }
+
+    match failed {
+        Some(error) => Err(format!("A post create step failed: {}", error)),
+        None => Ok(()),
+    }
+}
+
+/// Update the projects with an event from the workers
+pub fn apply_event(jobs: &mut [Job], event: BatchEvent) {
+    match event {
+        BatchEvent::Started(index) => jobs[index].status = JobStatus::Running,
+        BatchEvent::Output(index, line) => jobs[index].output = Some(line),
+        BatchEvent::Finished(index, Ok(())) => jobs[index].status = JobStatus::Succeeded,
+        BatchEvent::Finished(index, Err(error)) => jobs[index].status = JobStatus::Failed(error),
+    }
+}
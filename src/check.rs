@@ -0,0 +1,282 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use regex::Regex;
+
+use crate::error::AppError;
+use crate::hooks;
+use crate::metadata::{Metadata, METADATA_VARIABLES};
+use crate::overlay::PATCHES_DIR;
+use crate::plan::Plan;
+use crate::template::{self, Manifest, Template, TemplateError, FILES_DIR, MANIFEST_FILE, NAME_VARIANTS};
+
+/// The variables every template is given without declaring them
+const BUILTIN_VARIABLES: [&str; 2] = ["project_name", "project_folder"];
+/// The project name the template is rendered with while it is checked
+const EXAMPLE_NAME: &str = "example";
+
+/// How bad a problem found in a template is
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    /// The template can't be used as it is
+    Error,
+    /// The template works but is probably not what its author meant
+    Warning,
+}
+
+/// A problem found in a template, at a file and maybe a line
+#[derive(Clone, Debug)]
+pub struct Finding {
+    pub severity: Severity,
+    pub file: PathBuf,
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl Finding {
+    fn error(file: impl Into<PathBuf>, line: Option<usize>, message: impl Into<String>) -> Finding {
+        Finding { severity: Severity::Error, file: file.into(), line, message: message.into() }
+    }
+
+    fn warning(file: impl Into<PathBuf>, line: Option<usize>, message: impl Into<String>) -> Finding {
+        Finding { severity: Severity::Warning, file: file.into(), line, message: message.into() }
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        match self.line {
+            Some(line) => write!(f, "{}:{}: {}: {}", self.file.display(), line, severity, self.message),
+            None => write!(f, "{}: {}: {}", self.file.display(), severity, self.message),
+        }
+    }
+}
+
+/// Check a template for mistakes before it is published
+///
+/// The manifest, conditions and hook scripts are parsed, every `{{ }}` in
+/// the files is checked against the declared variables, and if nothing is
+/// wrong so far the template is rendered with its defaults into a temporary
+/// folder, where any TOML, JSON or YAML it writes is parsed too.
+pub fn check(root: &Path) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let manifest_path = root.join(MANIFEST_FILE);
+    let text = match fs::read_to_string(&manifest_path) {
+        Ok(text) => text,
+        Err(e) => return vec![Finding::error(manifest_path, None, e.to_string())],
+    };
+    if let Err(e) = toml::from_str::<Manifest>(&text) {
+        let line = e.span().map(|span| line_of(&text, span.start));
+        return vec![Finding::error(manifest_path, line, e.message())];
+    }
+    let template = match Template::load(root) {
+        Ok(template) => template,
+        Err(e) => return vec![Finding::error(manifest_path, None, e.to_string())],
+    };
+    let manifest = &template.manifest;
+
+    let mut declared: Vec<String> = BUILTIN_VARIABLES.iter().chain(&NAME_VARIANTS).chain(&METADATA_VARIABLES).map(|name| name.to_string()).collect();
+    for prompt in &manifest.prompts {
+        let line = find_line(&text, &format!("\"{}\"", prompt.name));
+        if declared.contains(&prompt.name) {
+            findings.push(Finding::error(&manifest_path, line, format!("`{}` is declared more than once", prompt.name)));
+        }
+        declared.push(prompt.name.clone());
+        if let Some(pattern) = &prompt.pattern
+            && Regex::new(pattern).is_err()
+        {
+            findings.push(Finding::error(&manifest_path, line, format!("`{}` has an invalid pattern `{}`", prompt.name, pattern)));
+        }
+    }
+
+    let mut conditions = Vec::new();
+    for prompt in &manifest.prompts {
+        if let Some(expression) = &prompt.when {
+            conditions.push((format!("`{}`", prompt.name), expression));
+        }
+    }
+    for (index, rule) in manifest.rules.iter().enumerate() {
+        if let Some(expression) = &rule.when {
+            conditions.push((format!("rule {}", index + 1), expression));
+        }
+    }
+    for (owner, expression) in &conditions {
+        if let Err(message) = hooks::check_condition(expression, &declared) {
+            findings.push(Finding::error(&manifest_path, find_line(&text, expression), format!("{} {}", owner, message)));
+        }
+    }
+
+    let mut scripts = Vec::new();
+    for script in manifest.hooks.pre_generate.iter().chain(&manifest.hooks.post_generate) {
+        let path = root.join(script);
+        match fs::read_to_string(&path) {
+            Ok(source) => {
+                if let Err((line, message)) = hooks::check_script(&source) {
+                    findings.push(Finding::error(&path, Some(line), message));
+                }
+                scripts.push(source);
+            }
+            Err(e) => findings.push(Finding::error(&path, None, e.to_string())),
+        }
+    }
+
+    if !manifest.post_create.is_empty() && !manifest.hooks.allow_commands {
+        let message = "`post_create` steps are only run if `[hooks]` sets `allow_commands = true`, or put them in the config file instead";
+        findings.push(Finding::warning(&manifest_path, find_line(&text, "post_create"), message));
+    }
+
+    let files = match source_files(root) {
+        Ok(files) => files,
+        Err(e) => {
+            findings.push(Finding::error(root, None, e.to_string()));
+            return findings;
+        }
+    };
+    let defined = defined_variables(&template, &declared);
+    let mut used = BTreeSet::new();
+    for (path, contents) in &files {
+        // Variables in a file's path have no line to point at
+        for (text, in_path) in [(path.to_string_lossy().as_ref(), true), (contents.as_str(), false)] {
+            let mut references = Vec::new();
+            let error = template::references(text, &mut references).err();
+            for (line, name) in references {
+                if defined.as_ref().is_some_and(|defined| !defined.contains(&name)) {
+                    findings.push(Finding::error(path, (!in_path).then_some(line), format!("undefined variable `{}`", name)));
+                }
+                used.insert(name);
+            }
+            if let Some((line, message)) = error {
+                findings.push(Finding::error(path, (!in_path).then_some(line), message));
+            }
+        }
+    }
+
+    for prompt in &manifest.prompts {
+        let pattern = Regex::new(&format!(r"\b{}\b", regex::escape(&prompt.name))).expect("escaped names are valid regexes");
+        let in_condition = conditions.iter().any(|(_, expression)| pattern.is_match(expression));
+        let in_script = scripts.iter().any(|source| pattern.is_match(source));
+        if !used.contains(&prompt.name) && !in_condition && !in_script {
+            let line = find_line(&text, &format!("\"{}\"", prompt.name));
+            findings.push(Finding::warning(&manifest_path, line, format!("`{}` is declared but never used", prompt.name)));
+        }
+    }
+
+    if !findings.iter().any(|finding| finding.severity == Severity::Error) {
+        findings.extend(render_defaults(&template, &files));
+    }
+    findings
+}
+
+/// Read every file the template renders, by its path in the template
+fn source_files(root: &Path) -> Result<BTreeMap<PathBuf, String>, TemplateError> {
+    let mut files = BTreeMap::new();
+    for dir in [FILES_DIR, PATCHES_DIR] {
+        let dir = root.join(dir);
+        if dir.is_dir() {
+            let mut plan = Plan::new(&dir);
+            template::collect_files(&dir, &dir, &mut plan)?;
+            files.extend(plan.files.into_iter().map(|file| (dir.join(file.path), file.contents)));
+        }
+    }
+    Ok(files)
+}
+
+/// Get the variables the files can use, the declared ones and any the pre generate hook adds
+///
+/// Nothing is returned if the hook fails, since which variables it would add can't be known.
+fn defined_variables(template: &Template, declared: &[String]) -> Option<BTreeSet<String>> {
+    let mut defined: BTreeSet<String> = declared.iter().cloned().collect();
+    if let Some(script) = &template.manifest.hooks.pre_generate {
+        let mut plan = Plan::new(Path::new(EXAMPLE_NAME));
+        plan.variables = example_variables();
+        template.resolve_prompts(&mut plan.variables).ok()?;
+        // A hook that fails to run is reported when the template is rendered
+        hooks::run_hook("pre_generate", &template.root.join(script), &mut plan, template.manifest.hooks.allow_commands).ok()?;
+        defined.extend(plan.variables.into_keys());
+    }
+    Some(defined)
+}
+
+/// Render the template with its defaults into a temporary folder and parse the structured files it writes
+fn render_defaults(template: &Template, files: &BTreeMap<PathBuf, String>) -> Vec<Finding> {
+    let manifest_path = template.root.join(MANIFEST_FILE);
+    let folder = std::env::temp_dir().join(format!("create-project-check-{}", process::id()));
+    let mut plan = match template.plan(&folder.join(EXAMPLE_NAME), example_variables()) {
+        Ok(plan) => plan,
+        Err(TemplateError::Render { file, line, message }) => {
+            return vec![Finding::error(template.root.join(FILES_DIR).join(file), Some(line), message)];
+        }
+        Err(TemplateError::Io(path, e)) => return vec![Finding::error(path, None, e.to_string())],
+        Err(e) => return vec![Finding::error(manifest_path, None, e.to_string())],
+    };
+
+    let mut findings = Vec::new();
+    let patches = template.root.join(PATCHES_DIR);
+    for (path, contents) in files.iter().filter(|(path, _)| path.starts_with(&patches)) {
+        let relative = path.strip_prefix(&patches).unwrap_or(path);
+        match (template::render(&relative.to_string_lossy(), &plan.variables), template::render(contents, &plan.variables)) {
+            (Ok(rendered), Ok(contents)) => plan.add_file(rendered, contents),
+            (Err((line, message)), _) | (_, Err((line, message))) => findings.push(Finding::error(path, Some(line), message)),
+        }
+    }
+
+    for file in &plan.files {
+        if let Err((line, message)) = parse_structured(&file.path, &file.contents) {
+            // Point at the template file the rendered file came from when the path wasn't templated
+            let source = [FILES_DIR, PATCHES_DIR]
+                .iter()
+                .map(|dir| template.root.join(dir).join(&file.path))
+                .find(|path| files.contains_key(path))
+                .unwrap_or_else(|| file.path.clone());
+            findings.push(Finding::error(source, line, format!("renders to invalid {}", message)));
+        }
+    }
+
+    if let Err(e) = fs::create_dir_all(&plan.root).map_err(AppError::io(&plan.root)).and_then(|_| plan.write_files()) {
+        findings.push(Finding::error(&plan.root, None, format!("couldn't write the rendered template: {}", e)));
+    }
+    let _ = fs::remove_dir_all(&folder);
+    findings
+}
+
+/// Parse a rendered TOML, JSON or YAML file, leaving any other kind of file alone
+/// ### Returns
+///  - The line, if known, and the message of the first syntax error
+fn parse_structured(path: &Path, text: &str) -> Result<(), (Option<usize>, String)> {
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("toml") => toml::from_str::<toml::Table>(text)
+            .map(|_| ())
+            .map_err(|e| (e.span().map(|span| line_of(text, span.start)), format!("TOML: {}", e.message()))),
+        Some("json") => serde_json::from_str::<serde_json::Value>(text)
+            .map(|_| ())
+            .map_err(|e| (Some(e.line()), format!("JSON: {}", e))),
+        Some("yaml" | "yml") => serde_yaml_ng::from_str::<serde_yaml_ng::Value>(text)
+            .map(|_| ())
+            .map_err(|e| (e.location().map(|location| location.line()), format!("YAML: {}", e))),
+        _ => Ok(()),
+    }
+}
+
+/// Get the variables every template is rendered with while it is checked
+fn example_variables() -> BTreeMap<String, String> {
+    let mut variables = BUILTIN_VARIABLES.iter().map(|name| (name.to_string(), EXAMPLE_NAME.to_string())).collect();
+    Metadata::default().add_variables(&mut variables);
+    variables
+}
+
+/// Get the line a byte offset is on, counting from 1
+fn line_of(text: &str, offset: usize) -> usize {
+    text[..offset.min(text.len())].matches('\n').count() + 1
+}
+
+/// Get the first line containing some text, counting from 1
+fn find_line(text: &str, needle: &str) -> Option<usize> {
+    text.find(needle).map(|offset| line_of(text, offset))
+}
@@ -0,0 +1,170 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::app::ProjectTypes;
+use crate::plan::Plan;
+
+/// Where the generated CI configuration is for
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CiTarget {
+    /// Don't generate any CI
+    #[default]
+    None,
+    /// A workflow in `.github/workflows/ci.yml`
+    #[serde(rename = "github-actions")]
+    GitHubActions,
+    /// A pipeline in `.gitlab-ci.yml`
+    #[serde(rename = "gitlab-ci")]
+    GitLabCi,
+    /// A plain `ci.sh` that can be run anywhere
+    Script,
+}
+
+impl CiTarget {
+    const ALL: [CiTarget; 4] = [CiTarget::None, CiTarget::GitHubActions, CiTarget::GitLabCi, CiTarget::Script];
+
+    /// Get the next target, wrapping around
+    pub fn next(self) -> CiTarget {
+        let index = CiTarget::ALL.iter().position(|t| *t == self).unwrap_or(0);
+        CiTarget::ALL[(index + 1) % CiTarget::ALL.len()]
+    }
+
+    /// Get the previous target, wrapping around
+    pub fn previous(self) -> CiTarget {
+        let index = CiTarget::ALL.iter().position(|t| *t == self).unwrap_or(0);
+        CiTarget::ALL[(index + CiTarget::ALL.len() - 1) % CiTarget::ALL.len()]
+    }
+}
+
+impl fmt::Display for CiTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CiTarget::None => write!(f, "None"),
+            CiTarget::GitHubActions => write!(f, "GitHub Actions"),
+            CiTarget::GitLabCi => write!(f, "GitLab CI"),
+            CiTarget::Script => write!(f, "ci.sh"),
+        }
+    }
+}
+
+/// A single named command in a pipeline
+struct CiStep {
+    name: &'static str,
+    run: &'static str,
+}
+
+/// Get the steps to build, lint and test a project type
+///
+/// pytest exits with 5 when it finds no tests, which a brand new project won't have yet
+fn ci_steps(project_type: ProjectTypes) -> Option<Vec<CiStep>> {
+    let steps = match project_type {
+        ProjectTypes::Python => vec![
+            CiStep { name: "Install tools", run: "python -m pip install pytest ruff" },
+            CiStep { name: "Build", run: "python -m compileall -q src" },
+            CiStep { name: "Lint", run: "ruff check ." },
+            CiStep { name: "Test", run: "python -m pytest || [ $? -eq 5 ]" },
+        ],
+        ProjectTypes::UvPython => vec![
+            CiStep { name: "Install dependencies", run: "uv sync" },
+            CiStep { name: "Lint", run: "uvx ruff check ." },
+            CiStep { name: "Test", run: "uv run --with pytest pytest || [ $? -eq 5 ]" },
+        ],
+        ProjectTypes::Rust => vec![
+            CiStep { name: "Build", run: "cargo build --verbose" },
+            CiStep { name: "Lint", run: "cargo clippy --all-targets -- -D warnings" },
+            CiStep { name: "Test", run: "cargo test --verbose" },
+        ],
+        ProjectTypes::CmakeCpp => vec![
+            CiStep { name: "Configure", run: "cmake -S . -B build" },
+            CiStep { name: "Build", run: "cmake --build build" },
+            CiStep { name: "Test", run: "ctest --test-dir build --output-on-failure" },
+        ],
+        ProjectTypes::Template(_) => return None,
+    };
+    Some(steps)
+}
+
+/// Whether CI can be generated for a project type
+pub fn supports(project_type: ProjectTypes) -> bool {
+    ci_steps(project_type).is_some()
+}
+
+/// Add the CI configuration for a project type to a plan
+pub fn add_ci(plan: &mut Plan, project_type: ProjectTypes, target: CiTarget) {
+    let Some(steps) = ci_steps(project_type) else {
+        return;
+    };
+
+    match target {
+        CiTarget::None => {}
+        CiTarget::GitHubActions => plan.add_file(".github/workflows/ci.yml", github_actions(project_type, &steps)),
+        CiTarget::GitLabCi => plan.add_file(".gitlab-ci.yml", gitlab_ci(project_type, &steps)),
+        CiTarget::Script => {
+            plan.add_file("ci.sh", script(project_type, &steps));
+            plan.mark_executable("ci.sh");
+        }
+    }
+}
+
+/// Render a GitHub Actions workflow
+fn github_actions(project_type: ProjectTypes, steps: &[CiStep]) -> String {
+    let setup = match project_type {
+        ProjectTypes::Python => "      - uses: actions/setup-python@v5\n        with:\n          python-version: \"3.x\"\n",
+        ProjectTypes::UvPython => "      - uses: astral-sh/setup-uv@v6\n",
+        ProjectTypes::Rust => "      - uses: dtolnay/rust-toolchain@stable\n        with:\n          components: clippy\n",
+        ProjectTypes::CmakeCpp | ProjectTypes::Template(_) => "",
+    };
+
+    let mut workflow = String::from(
+        "name: CI\n\non:\n  push:\n  pull_request:\n\njobs:\n  ci:\n    runs-on: ubuntu-latest\n    steps:\n      - uses: actions/checkout@v4\n",
+    );
+    workflow.push_str(setup);
+    for step in steps {
+        workflow.push_str(&format!("      - name: {}\n        run: {}\n", step.name, step.run));
+    }
+    workflow
+}
+
+/// Render a GitLab CI pipeline
+fn gitlab_ci(project_type: ProjectTypes, steps: &[CiStep]) -> String {
+    let (image, setup) = match project_type {
+        ProjectTypes::Python => ("python:3.12", None),
+        ProjectTypes::UvPython => ("ghcr.io/astral-sh/uv:python3.12-bookworm", None),
+        ProjectTypes::Rust => ("rust:latest", Some("rustup component add clippy")),
+        ProjectTypes::CmakeCpp | ProjectTypes::Template(_) => {
+            ("gcc:latest", Some("apt-get update && apt-get install -y cmake"))
+        }
+    };
+
+    let mut pipeline = format!("ci:\n  image: {}\n  script:\n", image);
+    if let Some(setup) = setup {
+        pipeline.push_str(&format!("    - {}\n", setup));
+    }
+    for step in steps {
+        pipeline.push_str(&format!("    - {}\n", yaml_quote(step.run)));
+    }
+    pipeline
+}
+
+/// Render a standalone `ci.sh`
+fn script(project_type: ProjectTypes, steps: &[CiStep]) -> String {
+    let mut script = String::from("#!/usr/bin/env bash\nset -euo pipefail\ncd \"$(dirname \"$0\")\"\n");
+    if project_type == ProjectTypes::Python {
+        script.push_str("\necho '==> Create virtual environment'\npython3 -m venv .venv\n. .venv/bin/activate\n");
+    }
+    for step in steps {
+        script.push_str(&format!("\necho '==> {}'\n{}\n", step.name, step.run));
+    }
+    script
+}
+
+/// Quote a command for a YAML list if it has characters YAML would misread
+fn yaml_quote(command: &str) -> String {
+    if command.contains(": ") || command.contains(" #") || command.starts_with(['[', '{', '*', '&', '!', '|', '>', '\'', '"', '%', '@']) {
+        format!("'{}'", command.replace('\'', "''"))
+    } else {
+        command.to_string()
+    }
+}
@@ -0,0 +1,245 @@
+use std::path::PathBuf;
+
+use crate::script::ScriptKind;
+use crate::shell::ShellKind;
+
+/// The help text printed for `--help` and after argument errors
+pub const USAGE: &str = "\
+Usage: create-project [OPTIONS]
+       create-project new --template <repo>[#[<ref>][:<dir>]] [OPTIONS]
+       create-project apply <recipe> [OPTIONS]
+       create-project batch <manifest> [OPTIONS]
+       create-project update [dir] [--dry-run]
+       create-project template check <dir>
+       create-project template from <dir> [--name <name>] [--output <dir>]
+       create-project init <bash|zsh|fish>
+
+Commands:
+  new                Start the wizard, the same as no command
+  apply <recipe>     Create the project described by a recipe without the TUI, the
+                     output options below apply to it too
+  batch <manifest>   Create every project listed in a manifest, several at a time,
+                     then print which were created and why any failed
+  update [dir]       Update the project in a folder, the current one if not given, to
+                     the latest version of its template, merging in your changes and
+                     asking about any that conflict
+  template check <dir>
+                     Check a template for mistakes: undefined or unused variables,
+                     conditions using undeclared variables, and syntax errors found
+                     by rendering it with its defaults
+  template from <dir>
+                     Make a template from an existing project, leaving out files git
+                     ignores and replacing the project's name with variables, it is
+                     written to the templates folder unless `--output` is given and
+                     the name is found from the project unless `--name` is given
+  init <shell>       Print a shell function that wraps create-project and cds into
+                     the project it creates, use it with:
+                       bash/zsh: eval \"$(create-project init bash)\"
+                       fish:     create-project init fish | source
+
+Options:
+  --recipe <path>    Pre-fill the wizard with the choices from a recipe
+  --template <repo>[#[<ref>][:<dir>]]
+                     Clone a template from a local git repository or `file://` URL,
+                     at a branch, tag or commit and from a folder inside it (use
+                     `#:<dir>` for a folder at the default branch), and start the
+                     wizard with it selected
+  --jobs <n>         How many projects `batch` creates at once, defaults to the
+                     manifest's `jobs` or the number of CPUs
+  --print-dir        Print the absolute path of the created project on exit
+  --cd-file <path>   Write the absolute path of the created project to a file on exit
+  --dry-run [format] Preview projects without writing anything, the plan of every
+                     confirmed project is printed on exit as `text` (default) or `json`
+  --emit-script <kind>
+                     Like --dry-run, but print each confirmed project as a standalone
+                     `bash` or `powershell` script that creates it
+  -h, --help         Print this help
+  -V, --version      Print the version";
+
+/// How the plans of a dry run are printed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DryRunFormat {
+    Text,
+    Json,
+}
+
+impl DryRunFormat {
+    /// Get the format with the given name
+    pub fn from_name(name: &str) -> Option<DryRunFormat> {
+        match name {
+            "text" => Some(DryRunFormat::Text),
+            "json" => Some(DryRunFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+/// The options for running the TUI
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TuiArgs {
+    /// Print the created project's folder to stdout on exit
+    pub print_dir: bool,
+    /// Write the created project's folder to this file on exit
+    pub cd_file: Option<PathBuf>,
+    /// Only preview projects, printing their plans in this format on exit
+    pub dry_run: Option<DryRunFormat>,
+    /// Only preview projects, printing them as scripts of this kind on exit
+    pub emit_script: Option<ScriptKind>,
+    /// A recipe to pre-fill the wizard with
+    pub recipe: Option<PathBuf>,
+    /// A git repository to fetch a template from and select
+    pub template: Option<String>,
+    /// How many projects `batch` creates at once
+    pub jobs: Option<usize>,
+}
+
+/// The options for making a template from a project
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TemplateFromArgs {
+    /// The project to make the template from
+    pub dir: PathBuf,
+    /// The project's name, instead of finding it from the project
+    pub name: Option<String>,
+    /// Where to write the template, instead of the templates folder
+    pub output: Option<PathBuf>,
+}
+
+/// What the program was asked to do on the command line
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CliCommand {
+    /// Run the TUI
+    Tui(TuiArgs),
+    /// Create a project from a recipe without the TUI
+    Apply(PathBuf, TuiArgs),
+    /// Create every project in a manifest
+    Batch(PathBuf, TuiArgs),
+    /// Update the project in a folder from its template
+    Update(PathBuf, TuiArgs),
+    /// Check the template in a folder
+    CheckTemplate(PathBuf),
+    /// Make a template from a project
+    TemplateFrom(TemplateFromArgs),
+    /// Print the shell integration for a shell
+    Init(ShellKind),
+    /// Print the help text
+    Help,
+    /// Print the version
+    Version,
+}
+
+/// Parse the command line arguments, not including the program name
+pub fn parse(args: impl IntoIterator<Item = String>) -> Result<CliCommand, String> {
+    let mut args = args.into_iter().peekable();
+    let mut tui = TuiArgs::default();
+    let mut apply = None;
+    let mut batch = None;
+    let mut update = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--dry-run" => {
+                // The format is optional, so only take the next argument if it is one
+                let format = args.peek().and_then(|format| DryRunFormat::from_name(format));
+                if format.is_some() {
+                    args.next();
+                }
+                tui.dry_run = Some(format.unwrap_or(DryRunFormat::Text));
+            }
+            "-h" | "--help" => return Ok(CliCommand::Help),
+            "-V" | "--version" => return Ok(CliCommand::Version),
+            "--print-dir" => tui.print_dir = true,
+            "--cd-file" => tui.cd_file = Some(PathBuf::from(value_of(&arg, args.next())?)),
+            "new" if apply.is_none() && batch.is_none() && update.is_none() => {}
+            "--template" => tui.template = Some(value_of(&arg, args.next())?),
+            "--recipe" => tui.recipe = Some(PathBuf::from(value_of(&arg, args.next())?)),
+            "--jobs" => tui.jobs = Some(jobs(&value_of(&arg, args.next())?)?),
+            "apply" if apply.is_none() && batch.is_none() && update.is_none() => apply = Some(PathBuf::from(value_of("apply", args.next())?)),
+            "batch" if apply.is_none() && batch.is_none() && update.is_none() => batch = Some(PathBuf::from(value_of("batch", args.next())?)),
+            "--emit-script" => tui.emit_script = Some(script_kind(&value_of(&arg, args.next())?)?),
+            "update" if apply.is_none() && batch.is_none() && update.is_none() => {
+                // The folder is optional, so only take the next argument if it isn't an option
+                let dir = args.next_if(|dir| !dir.starts_with('-'));
+                update = Some(PathBuf::from(dir.unwrap_or_else(|| ".".to_string())));
+            }
+            "template" if apply.is_none() && batch.is_none() && update.is_none() => {
+                return match value_of("template", args.next())?.as_str() {
+                    "check" => Ok(CliCommand::CheckTemplate(PathBuf::from(value_of("template check", args.next())?))),
+                    "from" => template_from(args).map(CliCommand::TemplateFrom),
+                    other => Err(format!("unknown template command `{}`", other)),
+                };
+            }
+            "init" => {
+                let shell = value_of("init", args.next())?;
+                let shell = ShellKind::from_name(&shell).ok_or_else(|| format!("unsupported shell `{}`", shell))?;
+                return Ok(CliCommand::Init(shell));
+            }
+            _ => {
+                if let Some(path) = arg.strip_prefix("--cd-file=") {
+                    tui.cd_file = Some(PathBuf::from(path));
+                } else if let Some(format) = arg.strip_prefix("--dry-run=") {
+                    let parsed = DryRunFormat::from_name(format);
+                    tui.dry_run = Some(parsed.ok_or_else(|| format!("unknown dry run format `{}`", format))?);
+                } else if let Some(count) = arg.strip_prefix("--jobs=") {
+                    tui.jobs = Some(jobs(count)?);
+                } else if let Some(spec) = arg.strip_prefix("--template=") {
+                    tui.template = Some(spec.to_string());
+                } else if let Some(path) = arg.strip_prefix("--recipe=") {
+                    tui.recipe = Some(PathBuf::from(path));
+                } else if let Some(kind) = arg.strip_prefix("--emit-script=") {
+                    tui.emit_script = Some(script_kind(kind)?);
+                } else {
+                    return Err(format!("unexpected argument `{}`", arg));
+                }
+            }
+        }
+    }
+
+    match (apply, batch, update) {
+        (Some(_), _, _) | (_, Some(_), _) | (_, _, Some(_)) if tui.recipe.is_some() || tui.template.is_some() => {
+            Err("`--recipe` and `--template` only work with the TUI".to_string())
+        }
+        (Some(recipe), _, _) => Ok(CliCommand::Apply(recipe, tui)),
+        (_, Some(manifest), _) => Ok(CliCommand::Batch(manifest, tui)),
+        (_, _, Some(dir)) => Ok(CliCommand::Update(dir, tui)),
+        (None, None, None) => Ok(CliCommand::Tui(tui)),
+    }
+}
+
+/// Parse the arguments of `template from`, after the command
+fn template_from(mut args: impl Iterator<Item = String>) -> Result<TemplateFromArgs, String> {
+    let mut from = TemplateFromArgs { dir: PathBuf::from(value_of("template from", args.next())?), ..TemplateFromArgs::default() };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--name" => from.name = Some(value_of(&arg, args.next())?),
+            "--output" => from.output = Some(PathBuf::from(value_of(&arg, args.next())?)),
+            _ => {
+                if let Some(name) = arg.strip_prefix("--name=") {
+                    from.name = Some(name.to_string());
+                } else if let Some(path) = arg.strip_prefix("--output=") {
+                    from.output = Some(PathBuf::from(path));
+                } else {
+                    return Err(format!("unexpected argument `{}`", arg));
+                }
+            }
+        }
+    }
+    Ok(from)
+}
+
+/// Get the script kind named on the command line
+fn script_kind(name: &str) -> Result<ScriptKind, String> {
+    ScriptKind::from_name(name).ok_or_else(|| format!("unknown script kind `{}`", name))
+}
+
+/// Parse the number of projects to create at once
+fn jobs(count: &str) -> Result<usize, String> {
+    match count.parse() {
+        Ok(count) if count > 0 => Ok(count),
+        _ => Err(format!("`--jobs` needs a positive number, not `{}`", count)),
+    }
+}
+
+/// Get the value that must follow an argument
+fn value_of(arg: &str, value: Option<String>) -> Result<String, String> {
+    value.ok_or_else(|| format!("`{}` needs a value", arg))
+}
@@ -0,0 +1,66 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+
+use serde::Deserialize;
+
+use crate::editor::EditorConfig;
+use crate::error::AppError;
+use crate::metadata::Metadata;
+use crate::paths;
+use crate::steps::PostCreateStep;
+use crate::template::Template;
+
+/// The name of the config file inside the config folder
+pub const CONFIG_FILE: &str = "config.toml";
+
+/// The commands to run after creating each built in project type
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct PostCreateConfig {
+    pub python: Vec<PostCreateStep>,
+    pub uv_python: Vec<PostCreateStep>,
+    pub rust: Vec<PostCreateStep>,
+    pub cmake_cpp: Vec<PostCreateStep>,
+    /// The commands to run after creating a project from a template or adding a feature, by the name of its folder
+    pub templates: BTreeMap<String, Vec<PostCreateStep>>,
+}
+
+impl PostCreateConfig {
+    /// Get the steps for a template or feature, the ones configured for it followed by its own
+    ///
+    /// A template's own `[[post_create]]` steps run programs just like its hooks can,
+    /// so they are left out unless the template declares `allow_commands`.
+    pub fn for_template(&self, template: &Template) -> Vec<PostCreateStep> {
+        let mut steps = self.templates.get(&template.id()).cloned().unwrap_or_default();
+        if template.manifest.hooks.allow_commands {
+            steps.extend(template.manifest.post_create.iter().cloned());
+        }
+        steps
+    }
+}
+
+/// The user's configuration, read from `config.toml`
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub post_create: PostCreateConfig,
+    pub editor: EditorConfig,
+    /// The metadata new projects start with, before git's config
+    pub metadata: Metadata,
+}
+
+impl Config {
+    /// Load the config file, a missing file gives the default config
+    pub fn load() -> Result<Config, AppError> {
+        let Some(path) = paths::config_dir().map(|dir| dir.join(CONFIG_FILE)) else {
+            return Ok(Config::default());
+        };
+
+        match fs::read_to_string(&path) {
+            Ok(text) => toml::from_str(&text).map_err(|e| AppError::ConfigParse(path, e.message().to_string())),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(e) => Err(AppError::Io(path, e)),
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use crate::app::ProjectTypes;
+use crate::plan::Plan;
+
+/// Whether container files can be generated for a project type
+pub fn supports(project_type: ProjectTypes) -> bool {
+    !matches!(project_type, ProjectTypes::Template(_))
+}
+
+/// Add a `Dockerfile`, `.dockerignore` and `.devcontainer/devcontainer.json` to a plan
+/// ### Parameters
+///  - plan: The plan to add the files to
+///  - project_type: The type of project the files are for
+///  - project_name: The name of the project, used as the binary name
+pub fn add_container(plan: &mut Plan, project_type: ProjectTypes, project_name: &str) {
+    if !supports(project_type) {
+        return;
+    }
+
+    plan.add_file("Dockerfile", dockerfile(project_type, project_name));
+    plan.add_file(".dockerignore", dockerignore(project_type));
+    plan.add_file(".devcontainer/devcontainer.json", devcontainer(project_type, project_name));
+}
+
+/// Render the Dockerfile for a project type
+fn dockerfile(project_type: ProjectTypes, project_name: &str) -> String {
+    match project_type {
+        ProjectTypes::Python => String::from(
+            "FROM python:3.12-slim\n\
+             \n\
+             ENV VIRTUAL_ENV=/opt/venv\n\
+             RUN python -m venv $VIRTUAL_ENV\n\
+             ENV PATH=\"$VIRTUAL_ENV/bin:$PATH\"\n\
+             \n\
+             WORKDIR /app\n\
+             COPY . .\n\
+             \n\
+             CMD [\"python\", \"src/main.py\"]\n",
+        ),
+        ProjectTypes::UvPython => String::from(
+            "FROM python:3.12-slim\n\
+             COPY --from=ghcr.io/astral-sh/uv:latest /uv /uvx /bin/\n\
+             \n\
+             WORKDIR /app\n\
+             COPY . .\n\
+             RUN uv sync --no-dev\n\
+             \n\
+             CMD [\"uv\", \"run\", \"main.py\"]\n",
+        ),
+        ProjectTypes::Rust => format!(
+            "FROM rust:1 AS build\n\
+             WORKDIR /app\n\
+             COPY . .\n\
+             RUN cargo build --release\n\
+             \n\
+             FROM debian:bookworm-slim\n\
+             COPY --from=build /app/target/release/{name} /usr/local/bin/{name}\n\
+             CMD [\"{name}\"]\n",
+            name = project_name
+        ),
+        ProjectTypes::CmakeCpp => format!(
+            "FROM debian:bookworm-slim AS build\n\
+             RUN apt-get update \\\n    && apt-get install -y --no-install-recommends build-essential cmake \\\n    && rm -rf /var/lib/apt/lists/*\n\
+             WORKDIR /app\n\
+             COPY . .\n\
+             RUN cmake -S . -B build -DCMAKE_BUILD_TYPE=Release && cmake --build build\n\
+             \n\
+             FROM debian:bookworm-slim\n\
+             COPY --from=build /app/build/{name} /usr/local/bin/{name}\n\
+             CMD [\"{name}\"]\n",
+            name = project_name
+        ),
+        ProjectTypes::Template(_) => String::new(),
+    }
+}
+
+/// Render the `.dockerignore` for a project type
+fn dockerignore(project_type: ProjectTypes) -> String {
+    let ignored = match project_type {
+        ProjectTypes::Python | ProjectTypes::UvPython => ".venv/\n__pycache__/\n*.pyc\n",
+        ProjectTypes::Rust => "target/\n",
+        ProjectTypes::CmakeCpp => "build/\n",
+        ProjectTypes::Template(_) => "",
+    };
+    format!(".git/\n.devcontainer/\nDockerfile\n{}", ignored)
+}
+
+/// Render the dev container config for a project type
+fn devcontainer(project_type: ProjectTypes, project_name: &str) -> String {
+    let (image, extensions, post_create) = match project_type {
+        ProjectTypes::Python => (
+            "mcr.microsoft.com/devcontainers/python:3.12",
+            "\"ms-python.python\"",
+            "python -m venv .venv",
+        ),
+        ProjectTypes::UvPython => (
+            "mcr.microsoft.com/devcontainers/python:3.12",
+            "\"ms-python.python\"",
+            "pipx install uv && uv sync",
+        ),
+        ProjectTypes::Rust => (
+            "mcr.microsoft.com/devcontainers/rust:1",
+            "\"rust-lang.rust-analyzer\"",
+            "cargo fetch",
+        ),
+        ProjectTypes::CmakeCpp => (
+            "mcr.microsoft.com/devcontainers/cpp:debian",
+            "\"ms-vscode.cpptools\", \"ms-vscode.cmake-tools\"",
+            "cmake -S . -B build",
+        ),
+        ProjectTypes::Template(_) => ("", "", ""),
+    };
+
+    format!(
+        "{{\n  \"name\": \"{}\",\n  \"image\": \"{}\",\n  \"postCreateCommand\": \"{}\",\n  \"customizations\": {{\n    \"vscode\": {{\n      \"extensions\": [{}]\n    }}\n  }}\n}}\n",
+        project_name, image, post_create, extensions
+    )
+}
@@ -0,0 +1,140 @@
+use std::env;
+use std::io;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use serde::Deserialize;
+
+use crate::app::ProjectTypes;
+use crate::plan::Plan;
+
+/// The editor settings from the config file
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct EditorConfig {
+    /// The IDE command to open projects with, the project folder is added as the last argument
+    pub ide: Vec<String>,
+    /// Whether the IDE runs inside the terminal (like `nvim`) rather than in its own window
+    pub ide_in_terminal: bool,
+}
+
+/// Get the user's editor from `$VISUAL` or `$EDITOR`, split into its arguments
+pub fn editor_command() -> Option<Vec<String>> {
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var| env::var(var).ok())
+        .map(|value| value.split_whitespace().map(str::to_string).collect::<Vec<_>>())
+        .find(|argv| !argv.is_empty())
+}
+
+/// Get the command that opens a folder in the system file manager
+pub fn file_manager_command() -> Vec<String> {
+    let program = if cfg!(target_os = "windows") {
+        "explorer"
+    } else if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+    vec![program.to_string()]
+}
+
+/// Run a command with the folder as its last argument and wait for it,
+/// the terminal must have been handed back to the command first
+pub fn run_in_terminal(argv: &[String], dir: &Path) -> io::Result<()> {
+    let Some((program, args)) = argv.split_first() else {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "empty command"));
+    };
+    Command::new(program).args(args).arg(dir).current_dir(dir).status()?;
+    Ok(())
+}
+
+/// Start a command with the folder as its last argument without waiting for it
+pub fn spawn_detached(argv: &[String], dir: &Path) -> io::Result<()> {
+    let Some((program, args)) = argv.split_first() else {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "empty command"));
+    };
+    Command::new(program)
+        .args(args)
+        .arg(dir)
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    Ok(())
+}
+
+/// Whether editor config can be generated for a project type
+pub fn supports(project_type: ProjectTypes) -> bool {
+    !matches!(project_type, ProjectTypes::Template(_))
+}
+
+/// Add `.editorconfig` and the VS Code settings and launch config to a plan
+/// ### Parameters
+///  - plan: The plan to add the files to
+///  - project_type: The type of project the files are for
+///  - project_name: The name of the project, used to find the binary to debug
+pub fn add_editor_config(plan: &mut Plan, project_type: ProjectTypes, project_name: &str) {
+    if !supports(project_type) {
+        return;
+    }
+
+    plan.add_file(".editorconfig", editorconfig(project_type));
+    plan.add_file(".vscode/settings.json", vscode_settings(project_type));
+    plan.add_file(".vscode/launch.json", vscode_launch(project_type, project_name));
+}
+
+/// Render the `.editorconfig` for a project type
+fn editorconfig(project_type: ProjectTypes) -> String {
+    let sources = match project_type {
+        ProjectTypes::Python | ProjectTypes::UvPython => "*.py",
+        ProjectTypes::Rust => "*.rs",
+        ProjectTypes::CmakeCpp => "{*.cpp,*.hpp,*.h,CMakeLists.txt}",
+        ProjectTypes::Template(_) => "*",
+    };
+    format!(
+        "root = true\n\n[*]\ncharset = utf-8\nend_of_line = lf\ninsert_final_newline = true\ntrim_trailing_whitespace = true\n\n[{}]\nindent_style = space\nindent_size = 4\n\n[*.{{json,yml,yaml,toml}}]\nindent_style = space\nindent_size = 2\n",
+        sources
+    )
+}
+
+/// Render the VS Code workspace settings for a project type
+fn vscode_settings(project_type: ProjectTypes) -> String {
+    let settings = match project_type {
+        ProjectTypes::Python | ProjectTypes::UvPython => {
+            "  \"python.defaultInterpreterPath\": \"${workspaceFolder}/.venv/bin/python\",\n  \"python.testing.pytestEnabled\": true,\n"
+        }
+        ProjectTypes::Rust => "  \"rust-analyzer.check.command\": \"clippy\",\n",
+        ProjectTypes::CmakeCpp => {
+            "  \"cmake.sourceDirectory\": \"${workspaceFolder}\",\n  \"cmake.buildDirectory\": \"${workspaceFolder}/build\",\n"
+        }
+        ProjectTypes::Template(_) => "",
+    };
+    format!("{{\n{}  \"editor.formatOnSave\": true\n}}\n", settings)
+}
+
+/// Render the VS Code launch config for a project type
+fn vscode_launch(project_type: ProjectTypes, project_name: &str) -> String {
+    let configuration = match project_type {
+        ProjectTypes::Python => {
+            "      \"type\": \"debugpy\",\n      \"request\": \"launch\",\n      \"program\": \"${workspaceFolder}/src/main.py\",\n      \"console\": \"integratedTerminal\"\n".to_string()
+        }
+        ProjectTypes::UvPython => {
+            "      \"type\": \"debugpy\",\n      \"request\": \"launch\",\n      \"program\": \"${workspaceFolder}/main.py\",\n      \"console\": \"integratedTerminal\"\n".to_string()
+        }
+        ProjectTypes::Rust => format!(
+            "      \"type\": \"lldb\",\n      \"request\": \"launch\",\n      \"cargo\": {{\n        \"args\": [\"build\", \"--bin={}\"]\n      }},\n      \"cwd\": \"${{workspaceFolder}}\"\n",
+            project_name
+        ),
+        ProjectTypes::CmakeCpp => format!(
+            "      \"type\": \"cppdbg\",\n      \"request\": \"launch\",\n      \"program\": \"${{workspaceFolder}}/build/{}\",\n      \"cwd\": \"${{workspaceFolder}}\",\n      \"MIMode\": \"gdb\"\n",
+            project_name
+        ),
+        ProjectTypes::Template(_) => String::new(),
+    };
+    format!(
+        "{{\n  \"version\": \"0.2.0\",\n  \"configurations\": [\n    {{\n      \"name\": \"Debug {}\",\n{}    }}\n  ]\n}}\n",
+        project_name, configuration
+    )
+}
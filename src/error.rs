@@ -0,0 +1,128 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::template::TemplateError;
+
+/// How many lines of a failed tool's output are kept to show the user
+const OUTPUT_LINES: usize = 10;
+
+/// The errors that can stop a project from being planned or created
+#[derive(Debug)]
+pub enum AppError {
+    /// A file or folder couldn't be read or written
+    Io(PathBuf, io::Error),
+    /// A tool the project needs isn't on PATH
+    ToolMissing(String),
+    /// A tool ran but failed, with the end of what it printed
+    ToolFailed { command: String, code: Option<i32>, output: String },
+    /// Something given in the wizard or a recipe isn't allowed
+    Validation(String),
+    /// A template, feature or README layout couldn't be rendered
+    TemplateRender(String),
+    /// The config file, a recipe or a manifest couldn't be parsed
+    ConfigParse(PathBuf, String),
+    /// One of the other errors, with what was being done when it happened
+    Context { context: String, error: Box<AppError> },
+}
+
+impl AppError {
+    /// Make an error for a failed tool, keeping only the end of its output
+    /// ### Parameters
+    ///  - command: The command as it would be typed into a shell
+    ///  - code: The exit code, `None` if the tool was killed by a signal
+    ///  - output: Everything the tool printed to stdout and stderr
+    pub fn tool_failed(command: String, code: Option<i32>, output: &str) -> AppError {
+        let lines: Vec<&str> = output.lines().filter(|line| !line.trim().is_empty()).collect();
+        let output = lines[lines.len().saturating_sub(OUTPUT_LINES)..].join("\n");
+        AppError::ToolFailed { command, code, output }
+    }
+
+    /// Say what was being done when the error happened, shown before its message
+    pub fn context(self, context: impl Into<String>) -> AppError {
+        AppError::Context { context: context.into(), error: Box::new(self) }
+    }
+
+    /// Make an error for an IO error while using a path
+    pub fn io(path: &Path) -> impl FnOnce(io::Error) -> AppError + '_ {
+        move |error| AppError::Io(path.to_path_buf(), error)
+    }
+
+    /// Get the short title the TUI shows the error under
+    pub fn title(&self) -> &'static str {
+        match self {
+            AppError::Io(..) => "Couldn't read or write a file",
+            AppError::ToolMissing(_) => "A tool is missing",
+            AppError::ToolFailed { .. } => "A command failed",
+            AppError::Validation(_) => "Invalid choice",
+            AppError::TemplateRender(_) => "Couldn't render the template",
+            AppError::ConfigParse(..) => "Couldn't read the config",
+            AppError::Context { error, .. } => error.title(),
+        }
+    }
+
+    /// Get the code the process exits with because of the error
+    ///
+    /// 1 is left for a command that ran but didn't succeed, like a batch with a failed
+    /// project, and 2 for a mistake on the command line.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            AppError::Io(..) => 3,
+            AppError::ToolMissing(_) => 4,
+            AppError::ToolFailed { .. } => 5,
+            AppError::Validation(_) => 6,
+            AppError::TemplateRender(_) => 7,
+            AppError::ConfigParse(..) => 8,
+            AppError::Context { error, .. } => error.exit_code(),
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::Io(path, error) => write!(f, "{}: {}", path.display(), error),
+            AppError::ToolMissing(tool) => write!(f, "{} not found on PATH, install it and try again", tool),
+            AppError::ToolFailed { command, code, output } => {
+                match code {
+                    Some(code) => write!(f, "`{}` failed with exit code {}", command, code)?,
+                    None => write!(f, "`{}` was stopped by a signal", command)?,
+                }
+                if !output.is_empty() {
+                    write!(f, ":\n{}", output)?;
+                }
+                Ok(())
+            }
+            AppError::Validation(message) | AppError::TemplateRender(message) => write!(f, "{}", message),
+            AppError::ConfigParse(path, message) => write!(f, "{}: {}", path.display(), message),
+            AppError::Context { context, error } => write!(f, "{}: {}", context, error),
+        }
+    }
+}
+
+impl Error for AppError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            AppError::Io(_, error) => Some(error),
+            AppError::Context { error, .. } => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<TemplateError> for AppError {
+    fn from(error: TemplateError) -> AppError {
+        match error {
+            TemplateError::Io(path, error) => AppError::Io(path, error),
+            TemplateError::Manifest(path, message) => AppError::ConfigParse(path, message),
+            TemplateError::Prompt { .. } => AppError::Validation(error.to_string()),
+            TemplateError::Render { .. } | TemplateError::Script { .. } => AppError::TemplateRender(error.to_string()),
+        }
+    }
+}
+
+/// Get the code the process exits with because of an error, 1 for anything that isn't an `AppError`
+pub fn exit_code(error: &(dyn Error + 'static)) -> i32 {
+    error.downcast_ref::<AppError>().map(AppError::exit_code).unwrap_or(1)
+}
@@ -0,0 +1,227 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use regex::Regex;
+
+use crate::error::AppError;
+use crate::paths;
+use crate::template::{self, FILES_DIR, MANIFEST_FILE};
+use crate::update::{ProjectRecord, RECORD_FILE};
+
+/// A template made from an existing project
+pub struct Extracted {
+    /// The folder the template was written to
+    pub root: PathBuf,
+    /// The project name that was replaced with variables
+    pub name: String,
+    /// Each file written, with how many times the name was replaced in it
+    pub files: Vec<(PathBuf, usize)>,
+    /// Files left out because templates can only hold text
+    pub skipped: Vec<PathBuf>,
+}
+
+/// Turn a project into a template, replacing its name with `{{ project_name }}` variables
+///
+/// Files ignored by git are left out. The name is looked for in snake, kebab
+/// and Pascal case, in both paths and contents, and anything that already
+/// looks like a variable is escaped so it is written out as it is.
+/// ### Parameters
+///  - dir: The project to make a template from
+///  - name: The project's name, found from the project if not given
+///  - output: The folder to write the template to, a new folder in the templates folder if not given
+pub fn extract(dir: &Path, name: Option<&str>, output: Option<&Path>) -> Result<Extracted, AppError> {
+    if !dir.is_dir() {
+        return Err(AppError::Validation(format!("{} isn't a folder", dir.display())));
+    }
+    let name = match name {
+        Some(name) => name.to_string(),
+        None => detect_name(dir),
+    };
+    let [snake, kebab, pascal] = template::name_variants(&name);
+    if snake.is_empty() {
+        return Err(AppError::Validation(format!("couldn't find the project's name in {}, give it with `--name`", dir.display())));
+    }
+
+    let root = match output {
+        Some(output) => output.to_path_buf(),
+        None => paths::templates_dir()
+            .ok_or_else(|| AppError::Validation("couldn't find the templates folder, give one with `--output`".to_string()))?
+            .join(&kebab),
+    };
+    if fs::read_dir(&root).is_ok_and(|mut entries| entries.next().is_some()) {
+        return Err(AppError::Validation(format!("{} already exists and isn't empty", root.display())));
+    }
+
+    // The forms a name is likely to be written in, longest first so no form is replaced inside another
+    let mut forms = vec![(pascal, "project_name_pascal"), (snake, "project_name_snake"), (kebab, "project_name_kebab")];
+    if !forms.iter().any(|(form, _)| *form == name) {
+        forms.push((name.clone(), "project_name"));
+    }
+    forms.sort_by_key(|(form, _)| std::cmp::Reverse(form.len()));
+    forms.dedup_by(|a, b| a.0 == b.0);
+    let pattern = forms.iter().map(|(form, _)| regex::escape(form)).collect::<Vec<_>>().join("|");
+    let pattern = Regex::new(&pattern).map_err(|e| AppError::Validation(e.to_string()))?;
+
+    let mut extracted = Extracted { root: root.clone(), name: name.clone(), files: Vec::new(), skipped: Vec::new() };
+    for path in project_files(dir)? {
+        let Ok(contents) = fs::read_to_string(dir.join(&path)) else {
+            extracted.skipped.push(path);
+            continue;
+        };
+        let (relative, path_count) = replace_name(&escape_braces(&path.to_string_lossy()), &pattern, &forms);
+        let (contents, count) = replace_name(&escape_braces(&contents), &pattern, &forms);
+
+        let target = root.join(FILES_DIR).join(relative);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(AppError::io(parent))?;
+        }
+        fs::write(&target, contents).map_err(AppError::io(&target))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            if let Ok(metadata) = fs::metadata(dir.join(&path)) {
+                let _ = fs::set_permissions(&target, fs::Permissions::from_mode(metadata.permissions().mode()));
+            }
+        }
+        extracted.files.push((path, path_count + count));
+    }
+
+    let manifest = format!(
+        "# Made by `create-project template from {}`, review it before using the template\n\
+         name = \"{}\"\n\
+         description = \"\"\n\
+         version = \"0.1.0\"\n\
+         \n\
+         # Every `{}` in the files was replaced with `{{{{ project_name }}}}` in the case it was written in.\n\
+         # Ask for more variables by adding prompts, for example:\n\
+         #\n\
+         # [[prompts]]\n\
+         # name = \"licence\"\n\
+         # type = \"choice\"\n\
+         # choices = [\"MIT\", \"Apache-2.0\"]\n",
+        dir.display(),
+        name.replace('\\', "\\\\").replace('"', "\\\""),
+        name,
+    );
+    let manifest_path = root.join(MANIFEST_FILE);
+    fs::write(&manifest_path, manifest).map_err(AppError::io(&manifest_path))?;
+    Ok(extracted)
+}
+
+/// Find the name of a project, from how it was created, its package manifest or its folder
+fn detect_name(dir: &Path) -> String {
+    if let Ok(record) = ProjectRecord::load(dir)
+        && let Some(name) = record.recipe.name
+    {
+        return name;
+    }
+    let from_manifest = |file: &str, table: &str| -> Option<String> {
+        let manifest: toml::Table = toml::from_str(&fs::read_to_string(dir.join(file)).ok()?).ok()?;
+        manifest.get(table)?.get("name")?.as_str().map(str::to_string)
+    };
+    from_manifest("Cargo.toml", "package")
+        .or_else(|| from_manifest("pyproject.toml", "project"))
+        .or_else(|| dir.canonicalize().ok()?.file_name().map(|name| name.to_string_lossy().into_owned()))
+        .unwrap_or_default()
+}
+
+/// List the files of a project that git wouldn't ignore, relative to it
+///
+/// Outside a git repository the `.gitignore` at the top of the project is read instead.
+fn project_files(dir: &Path) -> Result<Vec<PathBuf>, AppError> {
+    let output = Command::new("git")
+        .args(["ls-files", "-z", "--cached", "--others", "--exclude-standard"])
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output();
+    let mut files: Vec<PathBuf> = match output {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .split('\0')
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from)
+            // Files deleted but not yet staged are still listed
+            .filter(|path| dir.join(path).is_file())
+            .collect(),
+        _ => {
+            let ignored = gitignore(dir)?;
+            let mut files = Vec::new();
+            walk(dir, dir, &ignored, &mut files)?;
+            files
+        }
+    };
+    // The record is written again for every project made from the template
+    files.retain(|path| path != Path::new(RECORD_FILE));
+    files.sort();
+    Ok(files)
+}
+
+/// Read the patterns of the `.gitignore` at the top of a project, always ignoring `.git`
+///
+/// Negated patterns aren't supported and are left out.
+fn gitignore(dir: &Path) -> Result<Vec<Regex>, AppError> {
+    let text = fs::read_to_string(dir.join(".gitignore")).unwrap_or_default();
+    let patterns = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with('!'))
+        .chain([".git"]);
+    patterns
+        .map(|pattern| {
+            // A pattern without a slash in the middle matches at any depth
+            let anchored = pattern.trim_end_matches('/').contains('/');
+            let pattern = pattern.trim_start_matches('/');
+            let glob = if anchored { pattern.to_string() } else { format!("**/{}", pattern) };
+            template::glob_regex(&glob).map_err(|e| AppError::Validation(format!("{} {}", dir.join(".gitignore").display(), e)))
+        })
+        .collect()
+}
+
+/// Add every file under `current` that isn't ignored, relative to `dir`
+fn walk(dir: &Path, current: &Path, ignored: &[Regex], files: &mut Vec<PathBuf>) -> Result<(), AppError> {
+    let entries = fs::read_dir(current).map_err(AppError::io(current))?;
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        let relative = path.strip_prefix(dir).unwrap_or(&path).to_path_buf();
+        if ignored.iter().any(|pattern| pattern.is_match(&relative.to_string_lossy().replace('\\', "/"))) {
+            continue;
+        }
+        if path.is_dir() {
+            walk(dir, &path, ignored, files)?;
+        } else {
+            files.push(relative);
+        }
+    }
+    Ok(())
+}
+
+/// Escape every `{{` so it is written out as it is rather than read as a variable
+fn escape_braces(text: &str) -> String {
+    text.replace("{{", "\\{{")
+}
+
+/// Replace each form of the name with its variable
+///
+/// A form is only replaced where it isn't part of a longer lowercase word, so a
+/// name like `demo` doesn't change `demonstrate`.
+/// ### Returns
+///  - The text with the name replaced, and how many times it was replaced
+fn replace_name(text: &str, pattern: &Regex, forms: &[(String, &str)]) -> (String, usize) {
+    let mut output = String::with_capacity(text.len());
+    let mut count = 0;
+    let mut last = 0;
+    let joined = |c: Option<char>| c.is_some_and(|c| c.is_lowercase() || c.is_ascii_digit());
+    for found in pattern.find_iter(text) {
+        if joined(text[..found.start()].chars().next_back()) || joined(text[found.end()..].chars().next()) {
+            continue;
+        }
+        let variable = forms.iter().find(|(form, _)| form == found.as_str()).map(|(_, variable)| *variable).unwrap_or("project_name");
+        output.push_str(&text[last..found.start()]);
+        output.push_str(&format!("{{{{ {} }}}}", variable));
+        last = found.end();
+        count += 1;
+    }
+    output.push_str(&text[last..]);
+    (output, count)
+}
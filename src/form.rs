@@ -0,0 +1,114 @@
+use std::collections::BTreeMap;
+
+use crate::template::{Prompt, PromptType};
+
+/// The answers being given to a template's prompts on the form screen
+pub struct Form {
+    /// Every prompt of the template, including the ones that aren't asked yet
+    pub prompts: Vec<Prompt>,
+    /// The answers so far, along with the variables the prompts can refer to
+    pub variables: BTreeMap<String, String>,
+    /// The prompt that has focus
+    pub focus: usize,
+}
+
+impl Form {
+    /// Start a form, with any prompt that hasn't already been answered set to its default
+    pub fn new(prompts: Vec<Prompt>, mut variables: BTreeMap<String, String>) -> Form {
+        for prompt in &prompts {
+            if !variables.contains_key(&prompt.name) {
+                let value = prompt.default_value(&variables);
+                variables.insert(prompt.name.clone(), value);
+            }
+        }
+        let mut form = Form { prompts, variables, focus: 0 };
+        form.focus = form.visible().first().copied().unwrap_or(0);
+        form
+    }
+
+    /// Get the prompts that are asked given the current answers, in order
+    ///
+    /// A prompt whose condition can't be evaluated is shown so its error can be seen.
+    pub fn visible(&self) -> Vec<usize> {
+        (0..self.prompts.len())
+            .filter(|index| self.prompts[*index].is_asked(&self.variables, &self.prompts).unwrap_or(true))
+            .collect()
+    }
+
+    /// Get the current answer to a prompt
+    pub fn value(&self, index: usize) -> &str {
+        self.variables.get(&self.prompts[index].name).map(String::as_str).unwrap_or("")
+    }
+
+    /// Get what is wrong with a prompt's answer or condition, if anything
+    pub fn error(&self, index: usize) -> Option<String> {
+        let prompt = &self.prompts[index];
+        prompt
+            .is_asked(&self.variables, &self.prompts)
+            .and_then(|_| prompt.validate(self.value(index)))
+            .err()
+    }
+
+    /// Get the first prompt that is asked and has something wrong with it
+    pub fn first_error(&self) -> Option<usize> {
+        self.visible().into_iter().find(|index| self.error(*index).is_some())
+    }
+
+    /// Move the focus to the next or previous prompt that is asked, wrapping around the ends
+    pub fn move_focus(&mut self, forward: bool) {
+        let visible = self.visible();
+        if visible.is_empty() {
+            return;
+        }
+        let current = visible.iter().position(|index| *index == self.focus);
+        let next = match (current, forward) {
+            (Some(position), true) => (position + 1) % visible.len(),
+            (Some(position), false) => (position + visible.len() - 1) % visible.len(),
+            // The focused prompt was hidden by a change to an earlier answer
+            (None, _) => visible.iter().position(|index| *index > self.focus).unwrap_or(0),
+        };
+        self.focus = visible[next];
+    }
+
+    /// Type a character into the focused prompt, toggling it if it is a bool or choice
+    pub fn type_char(&mut self, c: char) {
+        match self.prompts[self.focus].kind {
+            PromptType::String => self.answer_mut().push(c),
+            PromptType::Integer if c.is_ascii_digit() || c == '-' && self.value(self.focus).is_empty() => self.answer_mut().push(c),
+            PromptType::Bool | PromptType::Choice if c == ' ' => self.change(true),
+            _ => {}
+        }
+    }
+
+    /// Delete the last character of the focused prompt
+    pub fn backspace(&mut self) {
+        if matches!(self.prompts[self.focus].kind, PromptType::String | PromptType::Integer) {
+            self.answer_mut().pop();
+        }
+    }
+
+    /// Toggle a bool or step through the choices of the focused prompt
+    pub fn change(&mut self, forward: bool) {
+        let prompt = &self.prompts[self.focus];
+        let value = match prompt.kind {
+            PromptType::Bool => (self.value(self.focus) != "true").to_string(),
+            PromptType::Choice if !prompt.choices.is_empty() => {
+                let count = prompt.choices.len();
+                let next = match prompt.choices.iter().position(|choice| choice == self.value(self.focus)) {
+                    Some(position) if forward => (position + 1) % count,
+                    Some(position) => (position + count - 1) % count,
+                    None => 0,
+                };
+                prompt.choices[next].clone()
+            }
+            _ => return,
+        };
+        *self.answer_mut() = value;
+    }
+
+    /// Get the focused prompt's answer to change it
+    fn answer_mut(&mut self) -> &mut String {
+        let name = self.prompts[self.focus].name.clone();
+        self.variables.entry(name).or_default()
+    }
+}
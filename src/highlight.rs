@@ -0,0 +1,292 @@
+use std::path::Path;
+
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+};
+
+/// The languages the preview knows how to highlight
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Language {
+    Rust,
+    Python,
+    Cpp,
+    Toml,
+    CMake,
+    Markdown,
+    Yaml,
+    Shell,
+    Json,
+    Plain,
+}
+
+impl Language {
+    /// Work out the language of a file from its name
+    pub fn detect(path: &Path) -> Language {
+        let name = path.file_name().map(|name| name.to_string_lossy().to_lowercase()).unwrap_or_default();
+        if name == "cmakelists.txt" {
+            return Language::CMake;
+        }
+        if name == "dockerfile" || name.starts_with('.') && name.ends_with("ignore") || name == ".editorconfig" {
+            return Language::Shell;
+        }
+
+        match path.extension().map(|extension| extension.to_string_lossy().to_lowercase()).as_deref() {
+            Some("rs") => Language::Rust,
+            Some("py") | Some("pyi") => Language::Python,
+            Some("cpp") | Some("cc") | Some("cxx") | Some("c") | Some("h") | Some("hpp") | Some("hh") => Language::Cpp,
+            Some("toml") => Language::Toml,
+            Some("cmake") => Language::CMake,
+            Some("md") | Some("markdown") => Language::Markdown,
+            Some("yml") | Some("yaml") => Language::Yaml,
+            Some("sh") | Some("bash") | Some("ps1") => Language::Shell,
+            Some("json") => Language::Json,
+            _ => Language::Plain,
+        }
+    }
+
+    /// The rules used to split a line of this language into tokens
+    fn syntax(self) -> Syntax {
+        match self {
+            Language::Rust => Syntax {
+                line_comment: Some("//"),
+                block_comment: Some(("/*", "*/")),
+                quotes: &['"'],
+                keywords: &[
+                    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+                    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+                    "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true", "type", "unsafe",
+                    "use", "where", "while",
+                ],
+            },
+            Language::Python => Syntax {
+                line_comment: Some("#"),
+                block_comment: Some(("\"\"\"", "\"\"\"")),
+                quotes: &['"', '\''],
+                keywords: &[
+                    "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del", "elif", "else",
+                    "except", "False", "finally", "for", "from", "global", "if", "import", "in", "is", "lambda", "None",
+                    "nonlocal", "not", "or", "pass", "raise", "return", "True", "try", "while", "with", "yield",
+                ],
+            },
+            Language::Cpp => Syntax {
+                line_comment: Some("//"),
+                block_comment: Some(("/*", "*/")),
+                quotes: &['"', '\''],
+                keywords: &[
+                    "auto", "bool", "break", "case", "catch", "char", "class", "const", "constexpr", "continue",
+                    "default", "delete", "do", "double", "else", "enum", "false", "float", "for", "if", "inline", "int",
+                    "long", "namespace", "new", "nullptr", "private", "protected", "public", "return", "short",
+                    "signed", "sizeof", "static", "struct", "switch", "template", "this", "throw", "true", "try",
+                    "typedef", "typename", "unsigned", "using", "virtual", "void", "while", "#include", "#define",
+                    "#ifdef", "#ifndef", "#endif", "#pragma",
+                ],
+            },
+            Language::Toml => Syntax {
+                line_comment: Some("#"),
+                block_comment: None,
+                quotes: &['"', '\''],
+                keywords: &["true", "false"],
+            },
+            Language::CMake => Syntax {
+                line_comment: Some("#"),
+                block_comment: None,
+                quotes: &['"'],
+                keywords: &[
+                    "if", "elseif", "else", "endif", "foreach", "endforeach", "while", "endwhile", "function",
+                    "endfunction", "macro", "endmacro", "ON", "OFF", "TRUE", "FALSE", "VERSION", "REQUIRED",
+                    "PUBLIC", "PRIVATE", "INTERFACE", "DESCRIPTION", "LANGUAGES", "CXX",
+                ],
+            },
+            Language::Yaml | Language::Shell => Syntax {
+                line_comment: Some("#"),
+                block_comment: None,
+                quotes: &['"', '\''],
+                keywords: &[
+                    "true", "false", "if", "then", "else", "fi", "for", "do", "done", "set", "echo", "cd", "FROM",
+                    "RUN", "COPY", "WORKDIR", "ENV", "CMD", "AS", "ENTRYPOINT",
+                ],
+            },
+            Language::Json => Syntax {
+                line_comment: None,
+                block_comment: None,
+                quotes: &['"'],
+                keywords: &["true", "false", "null"],
+            },
+            Language::Markdown | Language::Plain => Syntax {
+                line_comment: None,
+                block_comment: None,
+                quotes: &[],
+                keywords: &[],
+            },
+        }
+    }
+}
+
+/// How to split a language into tokens
+struct Syntax {
+    line_comment: Option<&'static str>,
+    block_comment: Option<(&'static str, &'static str)>,
+    quotes: &'static [char],
+    keywords: &'static [&'static str],
+}
+
+const COMMENT: Color = Color::DarkGray;
+const STRING: Color = Color::Green;
+const KEYWORD: Color = Color::Magenta;
+const NUMBER: Color = Color::Yellow;
+const FUNCTION: Color = Color::LightBlue;
+const HEADING: Color = Color::Cyan;
+const TEXT: Color = Color::Gray;
+
+/// Highlight the contents of a file, picking the language from its name
+pub fn highlight(path: &Path, text: &str) -> Vec<Line<'static>> {
+    let language = Language::detect(path);
+    match language {
+        Language::Markdown => text.lines().scan(false, |in_code, line| Some(markdown_line(line, in_code))).collect(),
+        Language::Plain => text.lines().map(|line| Line::from(Span::styled(line.to_string(), Style::default().fg(TEXT)))).collect(),
+        _ => {
+            let syntax = language.syntax();
+            let mut in_block = false;
+            text.lines()
+                .map(|line| code_line(line, &syntax, language, &mut in_block))
+                .collect()
+        }
+    }
+}
+
+/// Highlight one line of Markdown, tracking whether it is inside a code fence
+fn markdown_line(line: &str, in_code: &mut bool) -> Line<'static> {
+    let trimmed = line.trim_start();
+    if trimmed.starts_with("```") {
+        *in_code = !*in_code;
+        return Line::from(Span::styled(line.to_string(), Style::default().fg(COMMENT)));
+    }
+    if *in_code {
+        return Line::from(Span::styled(line.to_string(), Style::default().fg(STRING)));
+    }
+    if trimmed.starts_with('#') {
+        return Line::from(Span::styled(line.to_string(), Style::default().fg(HEADING).add_modifier(Modifier::BOLD)));
+    }
+
+    // Inline code in backticks
+    let mut spans = Vec::new();
+    for (index, part) in line.split('`').enumerate() {
+        let style = if index % 2 == 1 { Style::default().fg(STRING) } else { Style::default().fg(TEXT) };
+        let part = if index % 2 == 1 { format!("`{}`", part) } else { part.to_string() };
+        spans.push(Span::styled(part, style));
+    }
+    if let (true, Some(first)) = (trimmed.starts_with("- ") || trimmed.starts_with("* "), spans.first_mut()) {
+        first.style = first.style.fg(KEYWORD);
+    }
+    Line::from(spans)
+}
+
+/// Highlight one line of code, tracking whether it is inside a block comment
+fn code_line(line: &str, syntax: &Syntax, language: Language, in_block: &mut bool) -> Line<'static> {
+    let mut spans: Vec<Span<'static>> = Vec::new();
+    let mut rest = line;
+
+    // Tokens next to each other with the same colour are joined into one span
+    let push = |spans: &mut Vec<Span<'static>>, text: &str, colour: Color| match spans.last_mut() {
+        _ if text.is_empty() => {}
+        Some(last) if last.style.fg == Some(colour) => last.content.to_mut().push_str(text),
+        _ => spans.push(Span::styled(text.to_string(), Style::default().fg(colour))),
+    };
+
+    // TOML section headers are highlighted as a whole
+    if language == Language::Toml && line.trim_start().starts_with('[') {
+        push(&mut spans, line, HEADING);
+        return Line::from(spans);
+    }
+
+    while !rest.is_empty() {
+        if *in_block {
+            let Some((_, end)) = syntax.block_comment else {
+                *in_block = false;
+                continue;
+            };
+            match rest.find(end) {
+                Some(position) => {
+                    push(&mut spans, &rest[..position + end.len()], COMMENT);
+                    rest = &rest[position + end.len()..];
+                    *in_block = false;
+                }
+                None => {
+                    push(&mut spans, rest, COMMENT);
+                    rest = "";
+                }
+            }
+            continue;
+        }
+
+        if let Some((start, _)) = syntax.block_comment.filter(|(start, _)| rest.starts_with(start)) {
+            push(&mut spans, start, COMMENT);
+            rest = &rest[start.len()..];
+            *in_block = true;
+            continue;
+        }
+        if syntax.line_comment.is_some_and(|comment| rest.starts_with(comment)) {
+            push(&mut spans, rest, COMMENT);
+            break;
+        }
+
+        let first = rest.chars().next().unwrap_or(' ');
+        if syntax.quotes.contains(&first) {
+            let length = string_length(rest, first);
+            push(&mut spans, &rest[..length], STRING);
+            rest = &rest[length..];
+            continue;
+        }
+
+        if first.is_ascii_digit() {
+            let length = rest.find(|c: char| !(c.is_ascii_alphanumeric() || c == '.' || c == '_')).unwrap_or(rest.len());
+            push(&mut spans, &rest[..length], NUMBER);
+            rest = &rest[length..];
+            continue;
+        }
+
+        if first.is_alphabetic() || first == '_' || first == '#' && language == Language::Cpp {
+            let length = rest[first.len_utf8()..]
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .map(|position| position + first.len_utf8())
+                .unwrap_or(rest.len());
+            let word = &rest[..length];
+            let after = rest[length..].trim_start();
+
+            let colour = if syntax.keywords.contains(&word) {
+                KEYWORD
+            } else if after.starts_with('(') || language == Language::Rust && after.starts_with('!') {
+                FUNCTION
+            } else if matches!(language, Language::Toml | Language::Yaml) && (after.starts_with('=') || after.starts_with(':')) {
+                HEADING
+            } else {
+                TEXT
+            };
+            push(&mut spans, word, colour);
+            rest = &rest[length..];
+            continue;
+        }
+
+        push(&mut spans, &rest[..first.len_utf8()], TEXT);
+        rest = &rest[first.len_utf8()..];
+    }
+
+    Line::from(spans)
+}
+
+/// Get the length of the string literal at the start of the text, including
+/// its quotes, or the rest of the line if it isn't closed
+fn string_length(text: &str, quote: char) -> usize {
+    let mut escaped = false;
+    for (index, c) in text.char_indices().skip(1) {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == quote {
+            return index + c.len_utf8();
+        }
+    }
+    text.len()
+}
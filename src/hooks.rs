@@ -0,0 +1,150 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use rhai::module_resolvers::DummyModuleResolver;
+use rhai::{Array, Dynamic, Engine, EvalAltResult, Map, Scope};
+
+use crate::plan::{self, Plan, PlannedCommand};
+use crate::template::{Prompt, PromptType, TemplateError};
+
+/// Run a template hook script against a plan
+///
+/// The script sees two variables, `vars` (a map of the template variables)
+/// and `files` (a map of relative path to file contents), and any changes it
+/// makes to them are written back to the plan. Removing a key from `files`
+/// removes the file from the plan.
+///
+/// Scripts can't touch the disk or the network, and every path in `files` must stay
+/// inside the project folder. If the template declares
+/// `allow_commands`, they may call `run("program", ["args"])` to queue a
+/// command that runs in the project folder after the files are written.
+/// ### Parameters
+///  - hook: The name of the hook being run, used in errors
+///  - script: The path to the Rhai script
+///  - plan: The plan the hook can read and modify
+///  - allow_commands: Whether the script may queue commands
+pub fn run_hook(hook: &str, script: &Path, plan: &mut Plan, allow_commands: bool) -> Result<(), TemplateError> {
+    let source = fs::read_to_string(script).map_err(|e| TemplateError::Io(script.to_path_buf(), e))?;
+    let script_error = |message: String| TemplateError::Script { hook: hook.to_string(), message };
+
+    let queued = Rc::new(RefCell::new(Vec::new()));
+    let engine = sandboxed_engine(allow_commands, queued.clone());
+
+    let mut vars = Map::new();
+    for (name, value) in &plan.variables {
+        vars.insert(name.into(), value.clone().into());
+    }
+    let mut files = Map::new();
+    for file in &plan.files {
+        files.insert(file.path.to_string_lossy().replace('\\', "/").into(), file.contents.clone().into());
+    }
+
+    let mut scope = Scope::new();
+    scope.push("vars", vars);
+    scope.push("files", files);
+
+    engine
+        .run_with_scope(&mut scope, &source)
+        .map_err(|e| script_error(e.to_string()))?;
+
+    let vars = scope
+        .get_value::<Map>("vars")
+        .ok_or_else(|| script_error("`vars` must stay a map".to_string()))?;
+    let files = scope
+        .get_value::<Map>("files")
+        .ok_or_else(|| script_error("`files` must stay a map".to_string()))?;
+
+    plan.variables = vars
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+
+    let planned: Vec<PathBuf> = plan.files.iter().map(|file| file.path.clone()).collect();
+    for path in planned {
+        if !files.contains_key(path.to_string_lossy().replace('\\', "/").as_str()) {
+            plan.remove_file(&path);
+        }
+    }
+    for (path, contents) in files {
+        let path = PathBuf::from(path.as_str());
+        plan::check_path(&path).map_err(script_error)?;
+        plan.add_file(path, contents.to_string());
+    }
+
+    plan.post_commands.extend(queued.take());
+    Ok(())
+}
+
+/// Evaluate a condition from a template manifest, like a prompt's `when`
+///
+/// The expression sees every variable by name. Answers to `bool` and
+/// `integer` prompts are booleans and numbers, everything else is a string.
+pub fn condition(expression: &str, variables: &BTreeMap<String, String>, prompts: &[Prompt]) -> Result<bool, String> {
+    let engine = sandboxed_engine(false, Rc::default());
+    let mut scope = Scope::new();
+    for (name, value) in variables {
+        let value = match prompts.iter().find(|prompt| prompt.name == *name).map(|prompt| prompt.kind) {
+            Some(PromptType::Bool) => Dynamic::from(value == "true"),
+            Some(PromptType::Integer) => value.parse::<i64>().map(Dynamic::from).unwrap_or_else(|_| value.clone().into()),
+            _ => value.clone().into(),
+        };
+        scope.push_dynamic(name.clone(), value);
+    }
+
+    engine
+        .eval_expression_with_scope::<bool>(&mut scope, expression)
+        .map_err(|e| format!("has an invalid condition `{}`: {}", expression, e))
+}
+
+/// Check that a condition parses and only refers to the given variables, without evaluating it
+pub fn check_condition(expression: &str, variables: &[String]) -> Result<(), String> {
+    let mut engine = sandboxed_engine(false, Rc::default());
+    engine.set_strict_variables(true);
+    let mut scope = Scope::new();
+    for name in variables {
+        scope.push_dynamic(name.clone(), Dynamic::UNIT);
+    }
+
+    engine
+        .compile_expression_with_scope(&scope, expression)
+        .map(|_| ())
+        .map_err(|e| format!("has an invalid condition `{}`: {}", expression, e.0))
+}
+
+/// Check that a hook script parses, without running it
+/// ### Returns
+///  - The line and message of the first syntax error
+pub fn check_script(source: &str) -> Result<(), (usize, String)> {
+    sandboxed_engine(true, Rc::default())
+        .compile(source)
+        .map(|_| ())
+        .map_err(|e| (e.1.line().unwrap_or(1), e.0.to_string()))
+}
+
+/// Build a Rhai engine with no access to the outside world
+fn sandboxed_engine(allow_commands: bool, queued: Rc<RefCell<Vec<PlannedCommand>>>) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_module_resolver(DummyModuleResolver::new());
+    engine.disable_symbol("eval");
+    engine.set_max_operations(1_000_000);
+    engine.set_max_call_levels(64);
+    engine.set_max_expr_depths(64, 64);
+    engine.set_max_string_size(1 << 20);
+    // Printing would draw over the TUI
+    engine.on_print(|_| {});
+    engine.on_debug(|_, _, _| {});
+
+    engine.register_fn("run", move |program: &str, args: Array| -> Result<(), Box<EvalAltResult>> {
+        if !allow_commands {
+            return Err("this template does not declare `allow_commands`, so hooks can't run commands".into());
+        }
+        let args = args.into_iter().map(|arg: Dynamic| arg.to_string()).collect();
+        queued.borrow_mut().push(PlannedCommand { program: program.to_string(), args });
+        Ok(())
+    });
+
+    engine
+}
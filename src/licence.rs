@@ -0,0 +1,183 @@
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::plan::Plan;
+
+/// The licence a project is released under, named by its SPDX identifier
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Deserialize, Serialize)]
+pub enum Licence {
+    /// Don't add a licence
+    #[default]
+    #[serde(rename = "none")]
+    None,
+    #[serde(rename = "MIT")]
+    Mit,
+    #[serde(rename = "BSD-3-Clause")]
+    Bsd3Clause,
+    #[serde(rename = "ISC")]
+    Isc,
+    #[serde(rename = "Unlicense")]
+    Unlicense,
+}
+
+impl Licence {
+    const ALL: [Licence; 5] = [Licence::None, Licence::Mit, Licence::Bsd3Clause, Licence::Isc, Licence::Unlicense];
+
+    /// Get the next licence, wrapping around
+    pub fn next(self) -> Licence {
+        let index = Licence::ALL.iter().position(|l| *l == self).unwrap_or(0);
+        Licence::ALL[(index + 1) % Licence::ALL.len()]
+    }
+
+    /// Get the previous licence, wrapping around
+    pub fn previous(self) -> Licence {
+        let index = Licence::ALL.iter().position(|l| *l == self).unwrap_or(0);
+        Licence::ALL[(index + Licence::ALL.len() - 1) % Licence::ALL.len()]
+    }
+
+    /// Get the text of the licence with the year and copyright holder filled in
+    fn text(self, year: i64, holder: &str) -> Option<String> {
+        let text = match self {
+            Licence::None => return None,
+            Licence::Mit => MIT,
+            Licence::Bsd3Clause => BSD_3_CLAUSE,
+            Licence::Isc => ISC,
+            Licence::Unlicense => UNLICENSE,
+        };
+        Some(text.replace("{year}", &year.to_string()).replace("{holder}", holder))
+    }
+}
+
+impl fmt::Display for Licence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Licence::None => write!(f, "None"),
+            Licence::Mit => write!(f, "MIT"),
+            Licence::Bsd3Clause => write!(f, "BSD-3-Clause"),
+            Licence::Isc => write!(f, "ISC"),
+            Licence::Unlicense => write!(f, "Unlicense"),
+        }
+    }
+}
+
+/// Add a `LICENSE` file to a plan
+///
+/// The copyright holders are the project's authors, or just "The <name> authors" if it has none.
+pub fn add_licence(plan: &mut Plan, licence: Licence, project_name: &str, authors: &[String]) {
+    let holder = if authors.is_empty() { format!("The {} authors", project_name) } else { authors.join(", ") };
+    if let Some(text) = licence.text(current_year(), &holder) {
+        plan.add_file("LICENSE", text);
+    }
+}
+
+/// Get the current year in UTC
+fn current_year() -> i64 {
+    let days = SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.as_secs() / 86_400).unwrap_or(0) as i64;
+    // Howard Hinnant's `civil_from_days`, counting eras of 400 years from 0000-03-01
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    // Years start in March, so January and February belong to the next one
+    year_of_era + era * 400 + i64::from(month_index >= 10)
+}
+
+const MIT: &str = r#"MIT License
+
+Copyright (c) {year} {holder}
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+"#;
+
+const BSD_3_CLAUSE: &str = r#"BSD 3-Clause License
+
+Copyright (c) {year}, {holder}
+
+Redistribution and use in source and binary forms, with or without
+modification, are permitted provided that the following conditions are met:
+
+1. Redistributions of source code must retain the above copyright notice, this
+   list of conditions and the following disclaimer.
+
+2. Redistributions in binary form must reproduce the above copyright notice,
+   this list of conditions and the following disclaimer in the documentation
+   and/or other materials provided with the distribution.
+
+3. Neither the name of the copyright holder nor the names of its
+   contributors may be used to endorse or promote products derived from
+   this software without specific prior written permission.
+
+THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+"#;
+
+const ISC: &str = r#"ISC License
+
+Copyright (c) {year}, {holder}
+
+Permission to use, copy, modify, and/or distribute this software for any
+purpose with or without fee is hereby granted, provided that the above
+copyright notice and this permission notice appear in all copies.
+
+THE SOFTWARE IS PROVIDED "AS IS" AND THE AUTHOR DISCLAIMS ALL WARRANTIES
+WITH REGARD TO THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF
+MERCHANTABILITY AND FITNESS. IN NO EVENT SHALL THE AUTHOR BE LIABLE FOR
+ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL DAMAGES OR ANY DAMAGES
+WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS, WHETHER IN AN
+ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+"#;
+
+const UNLICENSE: &str = r#"This is free and unencumbered software released into the public domain.
+
+Anyone is free to copy, modify, publish, use, compile, sell, or
+distribute this software, either in source code form or as a compiled
+binary, for any purpose, commercial or non-commercial, and by any
+means.
+
+In jurisdictions that recognize copyright laws, the author or authors
+of this software dedicate any and all copyright interest in the
+software to the public domain. We make this dedication for the benefit
+of the public at large and to the detriment of our heirs and
+successors. We intend this dedication to be an overt act of
+relinquishment in perpetuity of all present and future rights to this
+software under copyright law.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT.
+IN NO EVENT SHALL THE AUTHORS BE LIABLE FOR ANY CLAIM, DAMAGES OR
+OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE,
+ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR
+OTHER DEALINGS IN THE SOFTWARE.
+
+For more information, please refer to <https://unlicense.org>
+"#;
@@ -0,0 +1,43 @@
+use crate::app::ProjectTypes;
+use crate::plan::Plan;
+
+/// The hooks every project gets in its pre-commit config
+const COMMON_HOOKS: &str = "  - repo: https://github.com/pre-commit/pre-commit-hooks\n    rev: v5.0.0\n    hooks:\n      - id: trailing-whitespace\n      - id: end-of-file-fixer\n      - id: check-yaml\n      - id: check-toml\n      - id: check-merge-conflict\n";
+
+/// Whether a formatter config can be generated for a project type
+pub fn supports_formatter(project_type: ProjectTypes) -> bool {
+    !matches!(project_type, ProjectTypes::Template(_))
+}
+
+/// Add a `.pre-commit-config.yaml` with the formatter and linter for a project type to a plan
+///
+/// Templates only get the hooks that work for any project.
+pub fn add_pre_commit(plan: &mut Plan, project_type: ProjectTypes) {
+    let hooks = match project_type {
+        ProjectTypes::Python | ProjectTypes::UvPython => {
+            "  - repo: https://github.com/astral-sh/ruff-pre-commit\n    rev: v0.6.9\n    hooks:\n      - id: ruff\n      - id: ruff-format\n"
+        }
+        ProjectTypes::Rust => {
+            "  - repo: local\n    hooks:\n      - id: cargo-fmt\n        name: cargo fmt\n        entry: cargo fmt --\n        language: system\n        types: [rust]\n      - id: cargo-clippy\n        name: cargo clippy\n        entry: cargo clippy --all-targets -- -D warnings\n        language: system\n        types: [rust]\n        pass_filenames: false\n"
+        }
+        ProjectTypes::CmakeCpp => {
+            "  - repo: https://github.com/pre-commit/mirrors-clang-format\n    rev: v19.1.1\n    hooks:\n      - id: clang-format\n        types_or: [c++, c]\n"
+        }
+        ProjectTypes::Template(_) => "",
+    };
+    plan.add_file(".pre-commit-config.yaml", format!("repos:\n{}{}", COMMON_HOOKS, hooks));
+}
+
+/// Add the formatter's config for a project type to a plan
+pub fn add_formatter(plan: &mut Plan, project_type: ProjectTypes) {
+    match project_type {
+        ProjectTypes::Python | ProjectTypes::UvPython => {
+            plan.add_file("ruff.toml", "line-length = 100\n\n[lint]\nextend-select = [\"I\", \"UP\", \"B\"]\n\n[format]\nquote-style = \"double\"\n");
+        }
+        ProjectTypes::Rust => plan.add_file("rustfmt.toml", "edition = \"2024\"\nmax_width = 100\n"),
+        ProjectTypes::CmakeCpp => {
+            plan.add_file(".clang-format", "BasedOnStyle: LLVM\nIndentWidth: 4\nColumnLimit: 100\nPointerAlignment: Left\n");
+        }
+        ProjectTypes::Template(_) => {}
+    }
+}
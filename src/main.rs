@@ -10,44 +10,544 @@
 #![allow(unused_imports)]
 #![allow(dead_code)]
 
-use std::{error::Error, io};
+use std::{
+    collections::BTreeSet,
+    env,
+    error::Error,
+    fs,
+    io::{self, IsTerminal},
+    path::{Path, PathBuf},
+    process,
+    sync::{atomic::{AtomicBool, Ordering}, mpsc::{Receiver, TryRecvError}, Arc},
+    thread,
+    time::Duration,
+};
 
 use ratatui::{
     backend::{Backend, CrosstermBackend}, crossterm::{
-        event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind},
-        execute,
-        terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+        event::{self, Event, KeyCode, KeyEventKind},
     }, Terminal
 };
 
 mod app;
+mod batch;
+mod check;
+mod ci;
+mod cli;
+mod config;
+mod container;
+mod editor;
+mod error;
+mod extract;
+mod form;
+mod highlight;
+mod hooks;
+mod licence;
+mod lint;
+mod metadata;
+mod merge;
+mod overlay;
+mod paths;
+mod plan;
+mod readme;
+mod recipe;
+mod scaffold;
+mod script;
+mod shell;
+mod source;
+mod steps;
+mod template;
+mod terminal;
+mod toolchain;
 mod ui;
+mod update;
 use crate::{
     app::{App, CurrentScreen, ProjectTypes},
+    batch::{BatchEvent, Job, JobStatus, Manifest},
+    check::Severity,
+    cli::{CliCommand, DryRunFormat, TemplateFromArgs, TuiArgs},
+    error::AppError,
+    plan::{display_command, Plan},
+    recipe::Recipe,
+    steps::StepEvent,
+    toolchain::{Availability, Toolchain},
     ui::ui,
+    update::FileChange,
 };
 
-fn main() -> Result<(), Box<dyn Error>> {
-    let _ = enable_raw_mode();
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+fn main() {
+    terminal::install_panic_hook();
+    if let Err(error) = terminal::install_signal_handlers() {
+        eprintln!("warning: couldn't listen for signals, the terminal may not be restored if interrupted: {}", error);
+    }
+
+    let command = match cli::parse(env::args().skip(1)) {
+        Ok(command) => command,
+        Err(error) => {
+            eprintln!("error: {}\n\n{}", error, cli::USAGE);
+            process::exit(2);
+        }
+    };
+
+    // `Ok(false)` is for commands that ran but didn't fully succeed, which have already said why
+    let result: Result<bool, Box<dyn Error>> = match command {
+        CliCommand::Help => {
+            println!("{}", cli::USAGE);
+            Ok(true)
+        }
+        CliCommand::Version => {
+            println!("create-project {}", env!("CARGO_PKG_VERSION"));
+            Ok(true)
+        }
+        CliCommand::Init(shell) => {
+            print!("{}", shell.init_script());
+            Ok(true)
+        }
+        CliCommand::Tui(args) => run_tui(args).map(|()| true),
+        CliCommand::Batch(manifest, args) => run_batch(&manifest, &args),
+        CliCommand::Apply(recipe, args) => run_apply(&recipe, &args).map(|()| true),
+        CliCommand::Update(dir, args) => run_update(&dir, &args),
+        CliCommand::CheckTemplate(dir) => Ok(run_check(&dir)),
+        CliCommand::TemplateFrom(args) => run_template_from(&args).map(|()| true).map_err(Into::into),
+    };
+
+    match result {
+        Ok(true) => {}
+        Ok(false) => process::exit(1),
+        Err(error) => {
+            eprintln!("error: {}", error);
+            process::exit(error::exit_code(error.as_ref()));
+        }
+    }
+}
+
+/// Check a template and print what is wrong with it, for `create-project template check`
+/// ### Returns
+///  - Whether the template has no errors, warnings are allowed
+fn run_check(dir: &Path) -> bool {
+    let findings = check::check(dir);
+    for finding in &findings {
+        println!("{}", finding);
+    }
+    let errors = findings.iter().filter(|finding| finding.severity == Severity::Error).count();
+    let warnings = findings.len() - errors;
+    if findings.is_empty() {
+        println!("{} looks good", dir.display());
+    } else {
+        let plural = |count: usize| if count == 1 { "" } else { "s" };
+        println!("{} error{}, {} warning{}", errors, plural(errors), warnings, plural(warnings));
+    }
+    errors == 0
+}
+
+/// Make a template from a project and check it, for `create-project template from`
+fn run_template_from(args: &TemplateFromArgs) -> Result<(), AppError> {
+    let extracted = extract::extract(&args.dir, args.name.as_deref(), args.output.as_deref())?;
+    for (path, replaced) in &extracted.files {
+        match replaced {
+            0 => println!("  copy  {}", path.display()),
+            _ => println!("  name  {} ({} replaced)", path.display(), replaced),
+        }
+    }
+    for path in &extracted.skipped {
+        println!("  skip  {} (not text)", path.display());
+    }
+    println!("Made a template from `{}` in {}", extracted.name, extracted.root.display());
+    println!("Review {} before using it, checking it now:", extracted.root.join(template::MANIFEST_FILE).display());
+    run_check(&extracted.root);
+    Ok(())
+}
 
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend).unwrap();
+/// Run the TUI, then report the created project as asked on the command line
+///
+/// An error left on screen when the TUI is closed is returned, so the exit code says what went wrong.
+fn run_tui(args: TuiArgs) -> Result<(), Box<dyn Error>> {
+    let setup = terminal::enter(true).and_then(|()| Terminal::new(CrosstermBackend::new(io::stdout())));
+    let mut terminal = match setup {
+        Ok(terminal) => terminal,
+        Err(e) => {
+            terminal::restore();
+            return Err(terminal_error(e).context("Couldn't start the TUI").into());
+        }
+    };
 
     // Create an instance of App and run it
     let mut app = App::new();
-    let _res = run_app(&mut terminal, &mut app);
+    app.dry_run = args.dry_run.is_some() || args.emit_script.is_some();
+    if let Some(Err(error)) = args.recipe.as_ref().map(|path| Recipe::load(path).and_then(|recipe| app.apply_recipe(&recipe))) {
+        app.error = Some(error);
+    }
+    if let Some(spec) = &args.template {
+        match app.add_git_template(spec) {
+            Ok(_) => {
+                app.current_screen = CurrentScreen::SelectProjectName;
+                app.prefill_input();
+            }
+            Err(error) => app.error = Some(error.context(format!("Couldn't fetch the template {}", spec))),
+        }
+    }
+    let result = run_app(&mut terminal, &mut app);
 
     // Restore terminal
-    let _ = disable_raw_mode();
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture,
-    )?;
-    let _ = terminal.show_cursor();
+    terminal::restore();
+    if let Some(code) = terminal::signal_exit_code() {
+        process::exit(code);
+    }
+    result.map_err(|e| terminal_error(e).context("The TUI stopped"))?;
+
+    if let Some(dir) = app.shell_dir.take() {
+        let error = shell::enter(&dir);
+        eprintln!("Couldn't start a shell in {}: {}", dir.display(), error);
+        return Ok(());
+    }
+
+    if let Some(dir) = &app.created_dir {
+        report_created_dir(&args, dir)?;
+    }
+
+    print_plans(&args, &app.dry_run_plans)?;
+
+    match app.error.take() {
+        Some(error) => Err(error.into()),
+        None => Ok(()),
+    }
+}
+
+/// Print the plans of a dry run in the format asked for on the command line
+fn print_plans(args: &TuiArgs, plans: &[Plan]) -> Result<(), Box<dyn Error>> {
+    match args.dry_run {
+        Some(DryRunFormat::Text) => {
+            let plans: Vec<String> = plans.iter().map(|plan| plan.to_text()).collect();
+            print!("{}", plans.join("\n"));
+        }
+        Some(DryRunFormat::Json) => {
+            println!("{}", serde_json::to_string_pretty(&plans)?);
+        }
+        None => {}
+    }
+    if let Some(kind) = args.emit_script {
+        let scripts: Vec<String> = plans.iter().map(|plan| script::emit(plan, kind)).collect();
+        print!("{}", scripts.join("\n"));
+    }
+
+    Ok(())
+}
+
+/// Create the project described by a recipe without the TUI, for `create-project apply`
+///
+/// Post create steps are run in order with their output passed through.
+fn run_apply(path: &Path, args: &TuiArgs) -> Result<(), Box<dyn Error>> {
+    let recipe = Recipe::load(path)?;
+    let mut app = App::new();
+    if let Some(error) = app.error.take() {
+        return Err(error.into());
+    }
+    let plan = app.plan_recipe(&recipe).map_err(|error| error.context(path.display().to_string()))?;
+
+    if args.dry_run.is_some() || args.emit_script.is_some() {
+        return print_plans(args, &[plan]);
+    }
+    if let (Availability::Missing(_), Some(tool)) = (app.toolchain.availability(app.project_type), Toolchain::main_tool(app.project_type)) {
+        return Err(AppError::ToolMissing(toolchain::program_name(tool).to_string()).into());
+    }
+    app.create_project()?;
+
+    let mut failed = None;
+    for event in steps::spawn(plan.post_create.clone(), &plan.root, Arc::new(AtomicBool::new(false))) {
+        match event {
+            StepEvent::Started(index) => eprintln!("$ {}", display_command(&plan.post_create[index].command)),
+            StepEvent::Output(_, line) => println!("{}", line),
+            StepEvent::Finished(_, Ok(())) => {}
+            StepEvent::Finished(index, Err(error)) => {
+                eprintln!("{}", error);
+                if plan.post_create[index].required {
+                    failed = Some(error);
+                }
+            }
+            StepEvent::Skipped(index) => eprintln!("Skipped {}", display_command(&plan.post_create[index].command)),
+        }
+    }
+
+    report_created_dir(args, &plan.root.canonicalize().unwrap_or_else(|_| plan.root.clone()))?;
+    match failed {
+        Some(error) => Err(format!("A post create step failed: {}", error).into()),
+        None => Ok(()),
+    }
+}
+
+/// Create every project in a manifest, for `create-project batch`
+///
+/// Projects whose recipes can't be planned fail straight away without stopping the others.
+/// ### Returns
+///  - Whether every project was created
+fn run_batch(path: &Path, args: &TuiArgs) -> Result<bool, Box<dyn Error>> {
+    let manifest = Manifest::load(path)?;
+    let mut app = App::new();
+    if let Some(error) = app.error.take() {
+        return Err(error.into());
+    }
+
+    let mut jobs = Vec::new();
+    let mut plans = Vec::new();
+    let mut folders = BTreeSet::new();
+    for (name, recipe) in manifest.recipes() {
+        let result = recipe.and_then(|recipe| app.plan_recipe(&recipe).map_err(|e| e.to_string())).and_then(|plan| {
+            if let Availability::Missing(reason) = app.toolchain.availability(app.project_type) {
+                return Err(reason);
+            }
+            // `a`, `./a` and its full path are the same folder, which two workers mustn't create at once
+            if !folders.insert(paths::absolute(&plan.root)) {
+                return Err(format!("{} is used by another project in the batch", plan.root.display()));
+            }
+            Ok(plan)
+        });
+        let (folder, status) = match result {
+            Ok(plan) => {
+                let folder = plan.root.display().to_string();
+                plans.push((jobs.len(), plan));
+                (folder, JobStatus::Waiting)
+            }
+            Err(error) => (String::new(), JobStatus::Failed(error)),
+        };
+        jobs.push(Job { name, folder, status, output: None });
+    }
+
+    if args.dry_run.is_some() || args.emit_script.is_some() {
+        for job in &jobs {
+            if let JobStatus::Failed(error) = &job.status {
+                eprintln!("{}: {}", job.name, error);
+            }
+        }
+        let plans: Vec<Plan> = plans.into_iter().map(|(_, plan)| plan).collect();
+        print_plans(args, &plans)?;
+        return Ok(plans.len() == jobs.len());
+    }
+
+    let workers = args
+        .jobs
+        .or(manifest.jobs)
+        .unwrap_or_else(|| thread::available_parallelism().map(|count| count.get()).unwrap_or(1));
+    let cancel = Arc::new(AtomicBool::new(false));
+    let events = batch::spawn(plans, workers, Arc::clone(&cancel));
+    if io::stdout().is_terminal() {
+        watch_batch(&mut jobs, &events, &cancel)?;
+    } else {
+        for event in events {
+            if let BatchEvent::Finished(index, result) = &event {
+                match result {
+                    Ok(()) => println!("created {} in {}", jobs[*index].name, jobs[*index].folder),
+                    Err(error) => println!("failed {}: {}", jobs[*index].name, error),
+                }
+            }
+            batch::apply_event(&mut jobs, event);
+        }
+    }
+
+    for job in &mut jobs {
+        if job.status == JobStatus::Waiting {
+            job.status = JobStatus::Cancelled;
+        }
+    }
+    let created = jobs.iter().filter(|job| job.status == JobStatus::Succeeded).count();
+    println!("\nCreated {} of {} projects", created, jobs.len());
+    for job in &jobs {
+        match &job.status {
+            JobStatus::Failed(error) => println!("  {}: {}", job.name, error),
+            JobStatus::Cancelled => println!("  {}: cancelled", job.name),
+            _ => {}
+        }
+    }
+    Ok(created == jobs.len())
+}
+
+/// Show the progress of a batch in the terminal until every worker has stopped
+///
+/// Pressing `q` or Ctrl+C stops new projects from being started, the running ones are left to finish.
+fn watch_batch(jobs: &mut [Job], events: &Receiver<BatchEvent>, cancel: &AtomicBool) -> Result<(), Box<dyn Error>> {
+    terminal::enter(false)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+
+    let result = (|| -> io::Result<()> {
+        loop {
+            loop {
+                match events.try_recv() {
+                    Ok(event) => batch::apply_event(jobs, event),
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => return Ok(()),
+                }
+            }
+            terminal.draw(|frame| ui::render_batch(frame, jobs, cancel.load(Ordering::Relaxed)))?;
 
+            if terminal::interrupted() {
+                return Ok(());
+            }
+            if event::poll(Duration::from_millis(50))?
+                && let Event::Key(key) = event::read()?
+                && (matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) || terminal::is_ctrl_c(&key))
+            {
+                cancel.store(true, Ordering::Relaxed);
+            }
+        }
+    })();
+
+    terminal::restore();
+    Ok(result?)
+}
+
+/// Update a project to the latest version of its template, for `create-project update`
+///
+/// Conflicts are decided in a TUI when there is a terminal, otherwise they are
+/// written with conflict markers. `--dry-run` only lists what would change.
+/// ### Returns
+///  - Whether the project was updated with no conflicts left in it
+fn run_update(dir: &Path, args: &TuiArgs) -> Result<bool, Box<dyn Error>> {
+    let (mut updates, record) = update::plan_update(dir)?;
+    for file in &updates {
+        println!("{:>8} {}", file.label(), file.path.display());
+    }
+    if args.dry_run.is_some() {
+        if updates.is_empty() {
+            println!("{} is up to date with its template", dir.display());
+        }
+        return Ok(true);
+    }
+
+    let conflicts = update::conflicts(&updates);
+    if !conflicts.is_empty() && io::stdout().is_terminal() && !review_conflicts(&mut updates, &conflicts)? {
+        println!("Cancelled, nothing was changed");
+        return Ok(false);
+    }
+    update::apply(dir, &updates, &record)?;
+
+    let unresolved = updates.iter().filter(|file| matches!(&file.change, FileChange::Merge(chunks) if merge::has_conflicts(chunks))).count();
+    if unresolved > 0 {
+        let summary = if unresolved == 1 { "1 file still has".to_string() } else { format!("{} files still have", unresolved) };
+        println!("{} conflicts, look for the <<<<<<< markers", summary);
+        return Ok(false);
+    }
+    if updates.is_empty() {
+        println!("{} is up to date with its template", dir.display());
+    } else {
+        println!("Updated {}", dir.display());
+    }
+    Ok(true)
+}
+
+/// Show the conflicts of an update so each can be decided
+/// ### Returns
+///  - Whether to write the update, any conflicts left undecided keep the project's lines
+fn review_conflicts(updates: &mut [update::FileUpdate], conflicts: &[(usize, usize)]) -> Result<bool, Box<dyn Error>> {
+    terminal::enter(false)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+    let mut selected = 0;
+
+    let result = (|| -> io::Result<bool> {
+        loop {
+            terminal.draw(|frame| ui::render_merge(frame, updates, conflicts, selected))?;
+            if terminal::interrupted() {
+                return Ok(false);
+            }
+            if !event::poll(Duration::from_millis(50))? {
+                continue;
+            }
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind == KeyEventKind::Release {
+                continue;
+            }
+            if terminal::is_ctrl_c(&key) {
+                return Ok(false);
+            }
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(false),
+                KeyCode::Up | KeyCode::Char('k') | KeyCode::BackTab => selected = (selected + conflicts.len() - 1) % conflicts.len(),
+                KeyCode::Down | KeyCode::Char('j') | KeyCode::Tab => selected = (selected + 1) % conflicts.len(),
+                KeyCode::Char(choice @ ('a' | 'r')) => {
+                    if let Some(conflict) = update::conflict_mut(updates, conflicts[selected]) {
+                        conflict.take_theirs = Some(choice == 'a');
+                    }
+                    selected = (selected + 1).min(conflicts.len() - 1);
+                }
+                KeyCode::Enter => {
+                    for location in conflicts {
+                        if let Some(conflict) = update::conflict_mut(updates, *location) {
+                            conflict.take_theirs.get_or_insert(false);
+                        }
+                    }
+                    return Ok(true);
+                }
+                _ => {}
+            }
+        }
+    })();
+
+    terminal::restore();
+    Ok(result?)
+}
+
+/// Print or write the created project's folder for `--print-dir` and `--cd-file`
+fn report_created_dir(args: &TuiArgs, dir: &Path) -> Result<(), AppError> {
+    if args.print_dir {
+        println!("{}", dir.display());
+    }
+    if let Some(cd_file) = &args.cd_file {
+        fs::write(cd_file, dir.to_string_lossy().as_bytes()).map_err(AppError::io(cd_file))?;
+    }
+    Ok(())
+}
+
+/// Make an error for the terminal failing, which has no path of its own
+fn terminal_error(error: io::Error) -> AppError {
+    AppError::Io(PathBuf::from("terminal"), error)
+}
+
+/// Take the terminal back after handing it to a program and redraw everything
+fn resume_terminal<B: Backend>(terminal: &mut Terminal<B>) -> io::Result<()> {
+    terminal::enter(true)?;
+    terminal.clear()
+}
+
+/// Open the created project in the editor, IDE or file manager
+/// ### Parameters
+///  - terminal: The terminal, handed over to the program if it runs in the terminal
+///  - app: The app holding the created project
+///  - key: The key that was pressed on the project created screen
+fn open_project<B: Backend>(terminal: &mut Terminal<B>, app: &mut App, key: KeyCode) -> io::Result<()> {
+    let Some(dir) = app.plan.as_ref().map(|plan| plan.root.clone()) else {
+        return Ok(());
+    };
+
+    let (name, argv, in_terminal) = match key {
+        KeyCode::Char('o') => ("editor", editor::editor_command(), true),
+        KeyCode::Char('i') => {
+            let ide = &app.config.editor.ide;
+            ("IDE", (!ide.is_empty()).then(|| ide.clone()), app.config.editor.ide_in_terminal)
+        }
+        _ => ("file manager", Some(editor::file_manager_command()), false),
+    };
+
+    let Some(argv) = argv else {
+        app.message = Some(match key {
+            KeyCode::Char('o') => "Set $VISUAL or $EDITOR to open the project in your editor".to_string(),
+            _ => "Set `ide` under [editor] in the config file to open the project in your IDE".to_string(),
+        });
+        return Ok(());
+    };
+
+    let result = if in_terminal {
+        terminal::restore();
+        let result = editor::run_in_terminal(&argv, &dir);
+        resume_terminal(terminal)?;
+        result
+    } else {
+        editor::spawn_detached(&argv, &dir)
+    };
+
+    app.message = Some(match result {
+        Ok(()) => format!("Opened the project in the {}", name),
+        Err(e) => format!("Couldn't open the {} `{}`: {}", name, argv.join(" "), e),
+    });
     Ok(())
 }
 
@@ -60,14 +560,34 @@ fn main() -> Result<(), Box<dyn Error>> {
 ///  - A boolean representing if the app should return successfully
 pub fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> io::Result<bool> {
     loop {
+        app.poll_steps();
         terminal.draw(|f| ui(f, app))?;
 
+        if terminal::interrupted() {
+            app.stop_steps();
+            return Ok(false);
+        }
+
+        // Wake up regularly so output from post create steps is drawn as it arrives
+        if !event::poll(Duration::from_millis(50))? {
+            continue;
+        }
+
         if let Event::Key(key) = event::read()? {
             if key.kind == event::KeyEventKind::Release {
                 continue;
             }
 
-            if key.code == KeyCode::Char('q') && app.current_screen != CurrentScreen::SelectProjectName && app.current_screen != CurrentScreen::SelectProjectFolder {
+            // Raw mode turns Ctrl+C into a key, it cancels the post create steps or quits from anywhere
+            if terminal::is_ctrl_c(&key) {
+                if app.steps_running() {
+                    app.cancel_steps();
+                    continue;
+                }
+                return Ok(false);
+            }
+
+            if key.code == KeyCode::Char('q') && app.current_screen != CurrentScreen::SelectProjectName && app.current_screen != CurrentScreen::SelectProjectFolder && app.current_screen != CurrentScreen::Metadata && app.current_screen != CurrentScreen::TemplatePrompts && !app.steps_running() {
                 return Ok(false);
             }
 
@@ -79,25 +599,16 @@ pub fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> io::Res
                 }
                 CurrentScreen::SelectProjectType => {
                     if key.code == KeyCode::Up {
-                        match app.project_type {
-                            ProjectTypes::Python => app.project_type = ProjectTypes::CmakeCpp,
-                            ProjectTypes::UvPython => app.project_type = ProjectTypes::Python,
-                            ProjectTypes::Rust => app.project_type = ProjectTypes::UvPython,
-                            ProjectTypes::CmakeCpp => app.project_type = ProjectTypes::Rust,
-                        }
+                        app.move_project_type(true);
                     }
 
                     if key.code == KeyCode::Down {
-                        match app.project_type {
-                            ProjectTypes::Python => app.project_type = ProjectTypes::UvPython,
-                            ProjectTypes::UvPython => app.project_type = ProjectTypes::Rust,
-                            ProjectTypes::Rust => app.project_type = ProjectTypes::CmakeCpp,
-                            ProjectTypes::CmakeCpp => app.project_type = ProjectTypes::Python,
-                        }
+                        app.move_project_type(false);
                     }
 
-                    if key.code == KeyCode::Enter {
+                    if key.code == KeyCode::Enter && app.can_create() {
                         app.current_screen = CurrentScreen::SelectProjectName;
+                        app.prefill_input();
                     }
                 }
                 CurrentScreen::SelectProjectName => {
@@ -108,13 +619,12 @@ pub fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> io::Res
                         KeyCode::Backspace => {
                             app.text_input.pop();
                         }
-                        KeyCode::Enter => {
-                            if !app.text_input.is_empty() {
-                                app.sanitise_input();
-                                app.project_name = Some(app.text_input.clone());
-                                app.current_screen = CurrentScreen::SelectProjectFolder;
-                                app.text_input.clear();
-                            }
+                        KeyCode::Enter if !app.text_input.is_empty() => {
+                            app.sanitise_input();
+                            app.project_name = Some(app.text_input.clone());
+                            app.current_screen = CurrentScreen::SelectProjectFolder;
+                            app.text_input.clear();
+                            app.prefill_input();
                         }
                         _ => {}
                     }
@@ -127,27 +637,98 @@ pub fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> io::Res
                         KeyCode::Backspace => {
                             app.text_input.pop();
                         }
+                        KeyCode::Enter if !app.text_input.is_empty() => {
+                            app.sanitise_input();
+                            app.project_folder = Some(app.text_input.clone());
+                            app.text_input.clear();
+                            app.start_metadata();
+                        }
+                        _ => {}
+                    }
+                }
+                CurrentScreen::Metadata | CurrentScreen::TemplatePrompts => {
+                    let Some(form) = &mut app.form else {
+                        continue;
+                    };
+                    match key.code {
+                        KeyCode::Tab | KeyCode::Down => form.move_focus(true),
+                        KeyCode::BackTab | KeyCode::Up => form.move_focus(false),
+                        KeyCode::Left => form.change(false),
+                        KeyCode::Right => form.change(true),
+                        KeyCode::Char(c) => form.type_char(c),
+                        KeyCode::Backspace => form.backspace(),
+                        KeyCode::Enter if app.current_screen == CurrentScreen::Metadata => app.finish_metadata(),
+                        KeyCode::Enter => app.finish_form(),
+                        _ => {}
+                    }
+                }
+                CurrentScreen::SelectProjectOptions => {
+                    match key.code {
+                        KeyCode::Up => app.move_option(true),
+                        KeyCode::Down => app.move_option(false),
+                        KeyCode::Left => app.change_option(false),
+                        KeyCode::Right | KeyCode::Char(' ') => app.change_option(true),
+                        KeyCode::Enter => app.start_features(),
+                        _ => {}
+                    }
+                }
+                CurrentScreen::SelectFeatures => {
+                    match key.code {
+                        KeyCode::Up => app.move_feature(true),
+                        KeyCode::Down => app.move_feature(false),
+                        KeyCode::Char(' ') => app.toggle_feature(),
+                        KeyCode::Char('a') => app.toggle_all_features(),
                         KeyCode::Enter => {
-                            if !app.text_input.is_empty() {
-                                app.sanitise_input();
-                                app.project_folder = Some(app.text_input.clone());
-                                app.current_screen = CurrentScreen::CreateProject;
-                                app.text_input.clear();
-                            }
+                            app.save_project();
+                            app.preview_selected = 0;
+                            app.preview_scroll = 0;
+                            app.current_screen = CurrentScreen::CreateProject;
                         }
                         _ => {}
                     }
                 }
                 CurrentScreen::CreateProject => {
-                    if key.code == KeyCode::Enter {
-                        app.save_project();
-                        app.create_project();
-                        app.current_screen = CurrentScreen::ProjectCreated;
+                    match key.code {
+                        KeyCode::Up => app.move_preview(true),
+                        KeyCode::Down => app.move_preview(false),
+                        KeyCode::PageUp => app.preview_scroll = app.preview_scroll.saturating_sub(10),
+                        KeyCode::PageDown => app.preview_scroll = app.preview_scroll.saturating_add(10),
+                        KeyCode::Char('x') => app.save_script(),
+                        KeyCode::Char('r') => app.save_recipe(),
+                        KeyCode::Enter => {
+                            if app.dry_run {
+                                if let Some(plan) = app.plan.clone() {
+                                    app.dry_run_plans.push(plan);
+                                }
+                            } else if app.error.is_none() {
+                                match app.create_project() {
+                                    Ok(()) => {
+                                        app.created_dir = app.plan.as_ref().map(|plan| plan.root.canonicalize().unwrap_or_else(|_| plan.root.clone()));
+                                        app.start_post_create();
+                                    }
+                                    Err(error) => app.error = Some(error),
+                                }
+                            }
+                            app.current_screen = CurrentScreen::ProjectCreated;
+                        }
+                        _ => {}
                     }
                 }
                 CurrentScreen::ProjectCreated => {
+                    if app.steps_running() {
+                        continue;
+                    }
                     match key.code {
+                        KeyCode::Char('o') | KeyCode::Char('i') | KeyCode::Char('f') if app.error.is_none() && !app.dry_run => {
+                            open_project(terminal, app, key.code)?;
+                        }
+                        KeyCode::Char('s') if app.error.is_none() && !app.dry_run => {
+                            app.shell_dir = app.plan.as_ref().map(|plan| plan.root.clone());
+                            return Ok(true);
+                        }
                         _ => {
+                            app.error = None;
+                            app.message = None;
                             app.current_screen = CurrentScreen::Main;
                         }
                     }
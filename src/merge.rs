@@ -0,0 +1,284 @@
+/// The most lines compared against each other when diffing, past this the changed part of a file is one hunk
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// A piece of a file merged from the project and the template
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Chunk {
+    /// Lines both sides agree on, or that only one side changed
+    Resolved(Vec<String>),
+    /// Lines the project and the template changed in different ways
+    Conflict(Conflict),
+}
+
+/// Lines the project and the template both changed, and which to keep
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Conflict {
+    /// The lines in the project
+    pub ours: Vec<String>,
+    /// The lines in the new version of the template
+    pub theirs: Vec<String>,
+    /// Whether to take the template's lines, the project's are kept until this is decided
+    pub take_theirs: Option<bool>,
+}
+
+/// Merge the project's and the template's changes to a file, line by line
+///
+/// Without a base every difference between the two is a conflict, since
+/// there is no way to tell which side changed it.
+/// ### Parameters
+///  - base: The file as the old version of the template wrote it
+///  - ours: The file as it is in the project
+///  - theirs: The file as the new version of the template writes it
+pub fn merge(base: Option<&str>, ours: &str, theirs: &str) -> Vec<Chunk> {
+    let known_base = base.is_some();
+    let ours = lines(ours);
+    let theirs = lines(theirs);
+    let base = match base {
+        Some(base) => lines(base),
+        // Comparing the project with itself makes every change look like it came from the template
+        None => ours.clone(),
+    };
+    let to_ours = matches(&base, &ours);
+    let to_theirs = matches(&base, &theirs);
+
+    let mut chunks = Vec::new();
+    let (mut b, mut o, mut t) = (0, 0, 0);
+    loop {
+        // Lines that are the same on every side
+        let mut stable = Vec::new();
+        while b < base.len() && to_ours[b] == Some(o) && to_theirs[b] == Some(t) {
+            stable.push(base[b].clone());
+            b += 1;
+            o += 1;
+            t += 1;
+        }
+        push_resolved(&mut chunks, stable);
+
+        // The next line that is the same on every side ends the changed part
+        let next = (b..base.len()).find_map(|index| match (to_ours[index], to_theirs[index]) {
+            (Some(o), Some(t)) => Some((index, o, t)),
+            _ => None,
+        });
+        let (end_b, end_o, end_t) = next.unwrap_or((base.len(), ours.len(), theirs.len()));
+        let (base_part, ours_part, theirs_part) = (&base[b..end_b], &ours[o..end_o], &theirs[t..end_t]);
+
+        if ours_part == theirs_part || known_base && theirs_part == base_part {
+            push_resolved(&mut chunks, ours_part.to_vec());
+        } else if known_base && ours_part == base_part {
+            push_resolved(&mut chunks, theirs_part.to_vec());
+        } else {
+            chunks.push(Chunk::Conflict(Conflict { ours: ours_part.to_vec(), theirs: theirs_part.to_vec(), take_theirs: None }));
+        }
+
+        if next.is_none() {
+            break;
+        }
+        (b, o, t) = (end_b, end_o, end_t);
+    }
+    chunks
+}
+
+/// Whether any part of a merge still needs a decision
+pub fn has_conflicts(chunks: &[Chunk]) -> bool {
+    chunks.iter().any(|chunk| matches!(chunk, Chunk::Conflict(Conflict { take_theirs: None, .. })))
+}
+
+/// Put a merged file back together
+///
+/// Conflicts that haven't been decided are written with `<<<<<<<` markers around both sides, like git does.
+pub fn join(chunks: &[Chunk]) -> String {
+    let mut text = String::new();
+    for chunk in chunks {
+        match chunk {
+            Chunk::Resolved(lines) => text.extend(lines.iter().map(String::as_str)),
+            Chunk::Conflict(Conflict { theirs, take_theirs: Some(true), .. }) => text.extend(theirs.iter().map(String::as_str)),
+            Chunk::Conflict(Conflict { ours, take_theirs: Some(false), .. }) => text.extend(ours.iter().map(String::as_str)),
+            Chunk::Conflict(Conflict { ours, theirs, take_theirs: None }) => {
+                text.push_str("<<<<<<< project\n");
+                push_lines(&mut text, ours);
+                text.push_str("=======\n");
+                push_lines(&mut text, theirs);
+                text.push_str(">>>>>>> template\n");
+            }
+        }
+    }
+    text
+}
+
+/// Split text into lines, keeping their line endings so joining them gives the text back
+pub fn lines(text: &str) -> Vec<String> {
+    text.split_inclusive('\n').map(str::to_string).collect()
+}
+
+/// Add lines inside conflict markers, ending the last one so the marker after it is on its own line
+fn push_lines(text: &mut String, lines: &[String]) {
+    for line in lines {
+        text.push_str(line);
+    }
+    if !text.ends_with('\n') {
+        text.push('\n');
+    }
+}
+
+/// Add resolved lines to a merge, joining them onto the chunk before if that is resolved too
+fn push_resolved(chunks: &mut Vec<Chunk>, lines: Vec<String>) {
+    if lines.is_empty() {
+        return;
+    }
+    match chunks.last_mut() {
+        Some(Chunk::Resolved(previous)) => previous.extend(lines),
+        _ => chunks.push(Chunk::Resolved(lines)),
+    }
+}
+
+/// Pair up the lines of two files that are kept between them, using their longest common subsequence
+/// ### Returns
+///  - The index in `b` of each line of `a`, if it is kept
+fn matches(a: &[String], b: &[String]) -> Vec<Option<usize>> {
+    let mut pairs = vec![None; a.len()];
+    let prefix = a.iter().zip(b).take_while(|(a, b)| a == b).count();
+    let suffix = a[prefix..].iter().rev().zip(b[prefix..].iter().rev()).take_while(|(a, b)| a == b).count();
+    for (index, pair) in pairs.iter_mut().enumerate().take(prefix) {
+        *pair = Some(index);
+    }
+    for offset in 1..=suffix {
+        pairs[a.len() - offset] = Some(b.len() - offset);
+    }
+
+    let (a_middle, b_middle) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+    let (rows, columns) = (a_middle.len(), b_middle.len());
+    if rows == 0 || columns == 0 || rows * columns > MAX_DIFF_CELLS {
+        return pairs;
+    }
+
+    // lengths[i][j] is the length of the longest common subsequence of a_middle[i..] and b_middle[j..]
+    let mut lengths = vec![0u32; (rows + 1) * (columns + 1)];
+    let cell = |i: usize, j: usize| i * (columns + 1) + j;
+    for i in (0..rows).rev() {
+        for j in (0..columns).rev() {
+            lengths[cell(i, j)] = if a_middle[i] == b_middle[j] {
+                lengths[cell(i + 1, j + 1)] + 1
+            } else {
+                lengths[cell(i + 1, j)].max(lengths[cell(i, j + 1)])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < rows && j < columns {
+        if a_middle[i] == b_middle[j] {
+            pairs[prefix + i] = Some(prefix + j);
+            i += 1;
+            j += 1;
+        } else if lengths[cell(i + 1, j)] >= lengths[cell(i, j + 1)] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolved(text: &str) -> Chunk {
+        Chunk::Resolved(lines(text))
+    }
+
+    fn conflict(ours: &str, theirs: &str) -> Chunk {
+        Chunk::Conflict(Conflict { ours: lines(ours), theirs: lines(theirs), take_theirs: None })
+    }
+
+    #[test]
+    fn unchanged_file_is_one_resolved_chunk() {
+        let text = "a\nb\nc\n";
+        assert_eq!(merge(Some(text), text, text), vec![resolved(text)]);
+    }
+
+    #[test]
+    fn changes_to_different_lines_merge_cleanly() {
+        let chunks = merge(Some("a\nb\nc\nd\ne\n"), "A\nb\nc\nd\ne\n", "a\nb\nc\nd\nE\n");
+        assert!(!has_conflicts(&chunks));
+        assert_eq!(join(&chunks), "A\nb\nc\nd\nE\n");
+    }
+
+    #[test]
+    fn only_the_template_changed() {
+        let chunks = merge(Some("a\nb\nc\n"), "a\nb\nc\n", "a\nB\nc\nd\n");
+        assert!(!has_conflicts(&chunks));
+        assert_eq!(join(&chunks), "a\nB\nc\nd\n");
+    }
+
+    #[test]
+    fn only_the_project_changed() {
+        let chunks = merge(Some("a\nb\nc\n"), "a\nc\nmine\n", "a\nb\nc\n");
+        assert!(!has_conflicts(&chunks));
+        assert_eq!(join(&chunks), "a\nc\nmine\n");
+    }
+
+    #[test]
+    fn the_same_change_on_both_sides_is_taken_once() {
+        let chunks = merge(Some("a\nb\nc\n"), "a\nB\nc\n", "a\nB\nc\n");
+        assert_eq!(chunks, vec![resolved("a\nB\nc\n")]);
+    }
+
+    #[test]
+    fn different_changes_to_the_same_lines_conflict() {
+        let chunks = merge(Some("a\nb\nc\n"), "a\nmine\nc\n", "a\ntheirs\nc\n");
+        assert_eq!(chunks, vec![resolved("a\n"), conflict("mine\n", "theirs\n"), resolved("c\n")]);
+        assert!(has_conflicts(&chunks));
+        assert_eq!(join(&chunks), "a\n<<<<<<< project\nmine\n=======\ntheirs\n>>>>>>> template\nc\n");
+    }
+
+    #[test]
+    fn deciding_a_conflict_picks_its_side() {
+        let mut chunks = merge(Some("a\nb\nc\n"), "a\nmine\nc\n", "a\ntheirs\nc\n");
+        let Chunk::Conflict(found) = &mut chunks[1] else {
+            panic!("expected a conflict, got {:?}", chunks[1]);
+        };
+        found.take_theirs = Some(true);
+        assert!(!has_conflicts(&chunks));
+        assert_eq!(join(&chunks), "a\ntheirs\nc\n");
+
+        let Chunk::Conflict(found) = &mut chunks[1] else {
+            unreachable!();
+        };
+        found.take_theirs = Some(false);
+        assert_eq!(join(&chunks), "a\nmine\nc\n");
+    }
+
+    #[test]
+    fn without_a_base_every_difference_conflicts() {
+        let chunks = merge(None, "a\nmine\nc\n", "a\ntheirs\nc\n");
+        assert_eq!(chunks, vec![resolved("a\n"), conflict("mine\n", "theirs\n"), resolved("c\n")]);
+
+        // Even a line only the template added, there is no way to tell who changed it
+        let chunks = merge(None, "a\nc\n", "a\nb\nc\n");
+        assert_eq!(chunks, vec![resolved("a\n"), conflict("", "b\n"), resolved("c\n")]);
+    }
+
+    #[test]
+    fn without_a_base_matching_files_merge_cleanly() {
+        let text = "a\nb\n";
+        assert_eq!(merge(None, text, text), vec![resolved(text)]);
+    }
+
+    #[test]
+    fn a_missing_last_newline_is_kept() {
+        let chunks = merge(Some("a\nb"), "a\nb", "A\nb");
+        assert_eq!(join(&chunks), "A\nb");
+
+        // The marker after a conflict still starts its own line
+        let chunks = merge(Some("a\nb"), "a\nmine", "a\ntheirs");
+        assert_eq!(join(&chunks), "a\n<<<<<<< project\nmine\n=======\ntheirs\n>>>>>>> template\n");
+    }
+
+    #[test]
+    fn matches_pairs_the_longest_common_subsequence() {
+        let a = lines("a\nb\nc\nd\n");
+        let b = lines("b\nx\nd\n");
+        assert_eq!(matches(&a, &b), vec![None, Some(0), None, Some(2)]);
+    }
+}
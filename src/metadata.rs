@@ -0,0 +1,256 @@
+use std::collections::BTreeMap;
+use std::process::{Command, Stdio};
+
+use serde::{Deserialize, Serialize};
+
+use crate::licence::Licence;
+use crate::template::{Prompt, PromptType};
+
+/// The version a project starts at when none is given
+pub const DEFAULT_VERSION: &str = "0.1.0";
+/// The variables templates are given from the metadata
+pub const METADATA_VARIABLES: [&str; 6] =
+    ["project_description", "project_authors", "project_email", "project_version", "project_repository", "project_keywords"];
+
+/// Details about a project that are written into its manifest and README
+///
+/// Every field is optional, the config file's `[metadata]` and git's config fill in what is left out.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Metadata {
+    /// A sentence saying what the project is
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    /// The people who wrote the project
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub authors: Vec<String>,
+    /// The email address of the first author
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub email: String,
+    /// The version the project starts at
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub version: String,
+    /// The URL of the project's homepage or repository
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub repository: String,
+    /// Words to find the project by in a package registry
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<String>,
+}
+
+impl Metadata {
+    /// Whether nothing has been given
+    pub fn is_empty(&self) -> bool {
+        *self == Metadata::default()
+    }
+
+    /// Fill in every field left empty from other metadata
+    pub fn or(self, defaults: &Metadata) -> Metadata {
+        let pick = |value: String, default: &String| if value.is_empty() { default.clone() } else { value };
+        let pick_list = |value: Vec<String>, default: &Vec<String>| if value.is_empty() { default.clone() } else { value };
+        Metadata {
+            description: pick(self.description, &defaults.description),
+            authors: pick_list(self.authors, &defaults.authors),
+            email: pick(self.email, &defaults.email),
+            version: pick(self.version, &defaults.version),
+            repository: pick(self.repository, &defaults.repository),
+            keywords: pick_list(self.keywords, &defaults.keywords),
+        }
+    }
+
+    /// Get the metadata used when nothing else is given, from the config file and then git's config
+    pub fn defaults(config: &Metadata) -> Metadata {
+        let from_git = Metadata {
+            authors: git_config("user.name").into_iter().collect(),
+            email: git_config("user.email").unwrap_or_default(),
+            version: DEFAULT_VERSION.to_string(),
+            ..Metadata::default()
+        };
+        config.clone().or(&from_git)
+    }
+
+    /// Get the version the project starts at
+    pub fn version(&self) -> &str {
+        if self.version.is_empty() { DEFAULT_VERSION } else { &self.version }
+    }
+
+    /// Get the authors as `Name <email>`, with the email on the first author
+    pub fn authors_with_email(&self) -> Vec<String> {
+        self.authors
+            .iter()
+            .enumerate()
+            .map(|(index, author)| match index {
+                0 if !self.email.is_empty() => format!("{} <{}>", author, self.email),
+                _ => author.clone(),
+            })
+            .collect()
+    }
+
+    /// Get the prompts of the metadata form
+    pub fn prompts() -> Vec<Prompt> {
+        let prompt = |name: &str, label: &str, help: &str, pattern: Option<&str>| Prompt {
+            name: name.to_string(),
+            kind: PromptType::String,
+            label: Some(label.to_string()),
+            help: help.to_string(),
+            pattern: pattern.map(str::to_string),
+            ..Prompt::default()
+        };
+        vec![
+            prompt("description", "Description", "A sentence saying what the project is", None),
+            prompt("authors", "Authors", "Separated by commas", None),
+            prompt("email", "Email", "The first author's email address", Some(r"([^@\s]+@[^@\s]+)?")),
+            prompt("version", "Version", "The version the project starts at", Some(r"\d+\.\d+\.\d+(-[0-9A-Za-z.-]+)?")),
+            prompt("repository", "Homepage / repository URL", "", Some(r"(https?://\S+)?")),
+            prompt("keywords", "Keywords", "Separated by commas", None),
+        ]
+    }
+
+    /// Check the metadata against the patterns of its form, for metadata that didn't come from the form
+    pub fn validate(&self) -> Result<(), String> {
+        let answers = self.to_answers();
+        for prompt in Metadata::prompts() {
+            prompt.validate(&answers[&prompt.name]).map_err(|message| format!("the metadata's `{}` {}", prompt.name, message))?;
+        }
+        Ok(())
+    }
+
+    /// Get the answers the metadata form starts with
+    pub fn to_answers(&self) -> BTreeMap<String, String> {
+        BTreeMap::from([
+            ("description".to_string(), self.description.clone()),
+            ("authors".to_string(), self.authors.join(", ")),
+            ("email".to_string(), self.email.clone()),
+            ("version".to_string(), self.version().to_string()),
+            ("repository".to_string(), self.repository.clone()),
+            ("keywords".to_string(), self.keywords.join(", ")),
+        ])
+    }
+
+    /// Read the metadata back from the answers to its form
+    pub fn from_answers(answers: &BTreeMap<String, String>) -> Metadata {
+        let text = |name: &str| answers.get(name).map(|value| value.trim().to_string()).unwrap_or_default();
+        let list = |name: &str| text(name).split(',').map(str::trim).filter(|item| !item.is_empty()).map(str::to_string).collect();
+        Metadata {
+            description: text("description"),
+            authors: list("authors"),
+            email: text("email"),
+            version: text("version"),
+            repository: text("repository"),
+            keywords: list("keywords"),
+        }
+    }
+
+    /// Add the metadata to a template's variables, unless they are already given
+    pub fn add_variables(&self, variables: &mut BTreeMap<String, String>) {
+        let values = [
+            self.description.clone(),
+            self.authors.join(", "),
+            self.email.clone(),
+            self.version().to_string(),
+            self.repository.clone(),
+            self.keywords.join(", "),
+        ];
+        for (variable, value) in METADATA_VARIABLES.into_iter().zip(values) {
+            variables.entry(variable.to_string()).or_insert(value);
+        }
+    }
+}
+
+/// Render the `Cargo.toml` of a binary crate, as `cargo init` writes it but with the metadata
+pub fn cargo_toml(project_name: &str, metadata: &Metadata, licence: Licence) -> String {
+    let mut text = format!(
+        "[package]\nname = {}\nversion = {}\nedition = \"2024\"\n",
+        toml_string(project_name),
+        toml_string(metadata.version())
+    );
+    if !metadata.description.is_empty() {
+        text.push_str(&format!("description = {}\n", toml_string(&metadata.description)));
+    }
+    if !metadata.authors.is_empty() {
+        text.push_str(&format!("authors = {}\n", toml_array(&metadata.authors_with_email())));
+    }
+    if licence != Licence::None {
+        text.push_str(&format!("license = {}\n", toml_string(&licence.to_string())));
+    }
+    if !metadata.repository.is_empty() {
+        text.push_str(&format!("repository = {}\n", toml_string(&metadata.repository)));
+    }
+    if !metadata.keywords.is_empty() {
+        text.push_str(&format!("keywords = {}\n", toml_array(&metadata.keywords)));
+    }
+    text.push_str("\n[dependencies]\n");
+    text
+}
+
+/// Render the `pyproject.toml` of an application, as `uv init --app` writes it but with the metadata
+pub fn pyproject_toml(project_name: &str, metadata: &Metadata, licence: Licence, requires_python: &str) -> String {
+    let mut text = format!("[project]\nname = {}\nversion = {}\n", toml_string(project_name), toml_string(metadata.version()));
+    if !metadata.description.is_empty() {
+        text.push_str(&format!("description = {}\n", toml_string(&metadata.description)));
+    }
+    text.push_str(&format!("readme = \"README.md\"\nrequires-python = {}\n", toml_string(&format!(">={}", requires_python))));
+    if !metadata.authors.is_empty() {
+        let authors: Vec<String> = metadata
+            .authors
+            .iter()
+            .enumerate()
+            .map(|(index, author)| match index {
+                0 if !metadata.email.is_empty() => format!("{{ name = {}, email = {} }}", toml_string(author), toml_string(&metadata.email)),
+                _ => format!("{{ name = {} }}", toml_string(author)),
+            })
+            .collect();
+        text.push_str(&format!("authors = [{}]\n", authors.join(", ")));
+    }
+    if licence != Licence::None {
+        text.push_str(&format!("license = {{ text = {} }}\n", toml_string(&licence.to_string())));
+    }
+    if !metadata.keywords.is_empty() {
+        text.push_str(&format!("keywords = {}\n", toml_array(&metadata.keywords)));
+    }
+    text.push_str("dependencies = []\n");
+    if !metadata.repository.is_empty() {
+        text.push_str(&format!("\n[project.urls]\nRepository = {}\n", toml_string(&metadata.repository)));
+    }
+    text
+}
+
+/// Render the `project()` call of a `CMakeLists.txt` with the metadata
+///
+/// `HOMEPAGE_URL` needs CMake 3.12, so the minimum version is raised when it is used.
+/// CMake's versions are only numbers, so a pre-release like `-rc1` is left out.
+/// ### Returns
+///  - The minimum CMake version, and the call
+pub fn cmake_project(project_name: &str, metadata: &Metadata) -> (&'static str, String) {
+    let quote = |text: &str| format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""));
+    let version = metadata.version().split(['-', '+']).next().unwrap_or_default();
+    let mut call = format!("project({} VERSION {}", project_name, version);
+    if !metadata.description.is_empty() {
+        call.push_str(&format!(" DESCRIPTION {}", quote(&metadata.description)));
+    }
+    let minimum = if metadata.repository.is_empty() {
+        "3.10"
+    } else {
+        call.push_str(&format!(" HOMEPAGE_URL {}", quote(&metadata.repository)));
+        "3.12"
+    };
+    call.push_str(" LANGUAGES CXX)");
+    (minimum, call)
+}
+
+/// Get a value from git's config
+pub fn git_config(key: &str) -> Option<String> {
+    let output = Command::new("git").args(["config", key]).stdin(Stdio::null()).stderr(Stdio::null()).output().ok()?;
+    let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !value.is_empty()).then_some(value)
+}
+
+/// Quote text as a TOML string
+fn toml_string(text: &str) -> String {
+    toml::Value::String(text.to_string()).to_string()
+}
+
+/// Write a list of text as a TOML array
+fn toml_array(items: &[String]) -> String {
+    format!("[{}]", items.iter().map(|item| toml_string(item)).collect::<Vec<_>>().join(", "))
+}
@@ -0,0 +1,282 @@
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::config::PostCreateConfig;
+use crate::error::AppError;
+use crate::plan::Plan;
+use crate::template::{self, Template, TemplateError};
+
+/// The folder inside an overlay holding the files merged into the project's files
+pub const PATCHES_DIR: &str = "patches";
+
+/// The `[overlay]` section of a template manifest, which makes it a feature added on top of a project
+///
+/// Features are named by the folder they are in.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OverlayManifest {
+    /// The project types, template folders or template names the feature can be added to, any if empty
+    pub applies_to: Vec<String>,
+    /// Features that are applied before this one when both are chosen
+    pub after: Vec<String>,
+    /// Features that can't be chosen along with this one
+    pub conflicts: Vec<String>,
+}
+
+/// Add the chosen features to a plan, one after another
+///
+/// Each feature's `files` are added and its `patches` are merged into the
+/// files already planned. Features are applied so every feature comes after
+/// the ones it lists in `after`, and otherwise in alphabetical order, so the
+/// same choices always give the same project.
+/// ### Parameters
+///  - plan: The plan of the base project
+///  - templates: Every template that was found, the ones with an `[overlay]` section are features
+///  - chosen: The names of the chosen features
+///  - base: The names the base project goes by, matched against `applies_to`
+///  - post_create: The post create steps from the config, for the features' own steps
+pub fn apply(
+    plan: &mut Plan,
+    templates: &[Template],
+    chosen: &BTreeSet<String>,
+    base: &[String],
+    post_create: &PostCreateConfig,
+) -> Result<(), AppError> {
+    let mut selected = Vec::new();
+    for id in chosen {
+        let (overlay, manifest) = templates
+            .iter()
+            .find_map(|template| template.manifest.overlay.as_ref().filter(|_| template.id() == *id).map(|manifest| (template, manifest)))
+            .ok_or_else(|| AppError::Validation(format!("there is no feature named `{}`", id)))?;
+        if !applies(manifest, base) {
+            return Err(AppError::Validation(format!("the `{}` feature can only be added to {}", id, manifest.applies_to.join(", "))));
+        }
+        if let Some(other) = manifest.conflicts.iter().find(|other| chosen.contains(*other)) {
+            return Err(AppError::Validation(format!("the `{}` and `{}` features can't be used together", id, other)));
+        }
+        selected.push((overlay, manifest));
+    }
+
+    for overlay in order(selected).map_err(AppError::Validation)? {
+        let mut patch = overlay.plan(&plan.root, plan.variables.clone())?;
+        for file in patch.files.drain(..) {
+            if plan.files.iter().any(|existing| existing.path == file.path) {
+                return Err(AppError::TemplateRender(format!(
+                    "the `{}` feature adds {}, which the project already has, it should be a patch",
+                    overlay.id(),
+                    file.path.display()
+                )));
+            }
+            plan.files.push(file);
+        }
+
+        let patches_root = overlay.root.join(PATCHES_DIR);
+        if patches_root.is_dir() {
+            let mut patches = Plan::new(&plan.root);
+            template::collect_files(&patches_root, &patches_root, &mut patches)?;
+            for file in patches.files {
+                let render_error = |(line, message)| {
+                    AppError::from(TemplateError::Render { file: patches_root.join(&file.path), line, message })
+                };
+                let contents = template::render(&file.contents, &patch.variables).map_err(render_error)?;
+                let path = template::render(&file.path.to_string_lossy(), &patch.variables).map_err(render_error)?;
+                match plan.files.iter_mut().find(|existing| existing.path == Path::new(&path)) {
+                    Some(existing) => {
+                        existing.contents = merge_file(Path::new(&path), &existing.contents, &contents)
+                            .map_err(|e| AppError::TemplateRender(format!("the `{}` feature couldn't patch {}: {}", overlay.id(), path, e)))?;
+                    }
+                    None => plan.add_file(path, contents),
+                }
+            }
+        }
+
+        for directory in patch.directories {
+            plan.add_directory(directory);
+        }
+        plan.post_commands.extend(patch.post_commands);
+        plan.post_create.extend(post_create.for_template(overlay));
+        plan.skipped.extend(patch.skipped);
+    }
+    Ok(())
+}
+
+/// Whether a feature can be added to a project going by any of the given names
+pub fn applies(manifest: &OverlayManifest, base: &[String]) -> bool {
+    manifest.applies_to.is_empty() || manifest.applies_to.iter().any(|name| base.contains(name))
+}
+
+/// Sort the features so each one comes after the ones in its `after`, breaking ties alphabetically
+fn order<'a>(mut remaining: Vec<(&'a Template, &OverlayManifest)>) -> Result<Vec<&'a Template>, String> {
+    remaining.sort_by_key(|(overlay, _)| overlay.id());
+    let mut ordered = Vec::new();
+    while !remaining.is_empty() {
+        // A feature is ready once nothing it must come after is still waiting
+        let ready = remaining
+            .iter()
+            .position(|(_, manifest)| !manifest.after.iter().any(|id| remaining.iter().any(|(other, _)| other.id() == *id)));
+        match ready {
+            Some(index) => ordered.push(remaining.remove(index).0),
+            None => {
+                let ids: Vec<String> = remaining.iter().map(|(overlay, _)| overlay.id()).collect();
+                return Err(format!("the features {} must each come after another, so none can go first", ids.join(", ")));
+            }
+        }
+    }
+    Ok(ordered)
+}
+
+/// Merge a patch into a file, by keys for TOML, JSON and YAML
+fn merge_file(path: &Path, base: &str, patch: &str) -> Result<String, String> {
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("toml") => {
+            // Edited in place so the file keeps its comments and formatting
+            let mut base: toml_edit::DocumentMut = base.parse().map_err(|e: toml_edit::TomlError| e.message().to_string())?;
+            let patch: toml_edit::DocumentMut = patch.parse().map_err(|e: toml_edit::TomlError| format!("the patch: {}", e.message()))?;
+            merge_toml(base.as_item_mut(), patch.into_item());
+            Ok(base.to_string())
+        }
+        Some("json") => {
+            let mut base: serde_json::Value = serde_json::from_str(base).map_err(|e| e.to_string())?;
+            let patch: serde_json::Value = serde_json::from_str(patch).map_err(|e| format!("the patch: {}", e))?;
+            merge_json(&mut base, patch);
+            serde_json::to_string_pretty(&base).map(|text| text + "\n").map_err(|e| e.to_string())
+        }
+        Some("yaml" | "yml") => {
+            let mut base: serde_yaml_ng::Value = serde_yaml_ng::from_str(base).map_err(|e| e.to_string())?;
+            let patch: serde_yaml_ng::Value = serde_yaml_ng::from_str(patch).map_err(|e| format!("the patch: {}", e))?;
+            merge_yaml(&mut base, patch);
+            // Unlike TOML, YAML is written back out from the merged values, so comments and formatting are lost
+            serde_yaml_ng::to_string(&base).map_err(|e| e.to_string())
+        }
+        _ => Err("only TOML, JSON and YAML files can be patched".to_string()),
+    }
+}
+
+/// Merge TOML tables key by key, adding array items that aren't there yet and replacing anything else
+fn merge_toml(base: &mut toml_edit::Item, patch: toml_edit::Item) {
+    if let Some(table) = base.as_table_like_mut()
+        && patch.is_table_like()
+    {
+        for (key, value) in patch.into_table().into_iter().flatten() {
+            match table.get_mut(&key) {
+                Some(existing) => merge_toml(existing, value),
+                None => {
+                    table.insert(&key, value);
+                }
+            }
+        }
+        return;
+    }
+
+    match (base, patch) {
+        (toml_edit::Item::Value(toml_edit::Value::Array(base)), toml_edit::Item::Value(toml_edit::Value::Array(patch))) => {
+            // New items are spaced like the last one, so they fit in single and multi line arrays
+            let decor = base.iter().last().map(|last| last.decor().clone()).unwrap_or_default();
+            for mut item in patch {
+                if !base.iter().any(|existing| same_toml(&existing.to_string(), &item.to_string())) {
+                    *item.decor_mut() = decor.clone();
+                    base.push_formatted(item);
+                }
+            }
+        }
+        (toml_edit::Item::ArrayOfTables(base), toml_edit::Item::ArrayOfTables(patch)) => {
+            for table in patch {
+                if !base.iter().any(|existing| same_toml(&existing.to_string(), &table.to_string())) {
+                    base.push(table);
+                }
+            }
+        }
+        (toml_edit::Item::Value(base), toml_edit::Item::Value(patch)) => {
+            // The value is replaced but keeps the spacing and comment around it
+            let decor = base.decor().clone();
+            *base = patch;
+            *base.decor_mut() = decor;
+        }
+        (base, patch) => *base = patch,
+    }
+}
+
+/// Whether two TOML values or tables are the same, ignoring their formatting
+fn same_toml(a: &str, b: &str) -> bool {
+    match (a.trim().parse::<toml::Value>(), b.trim().parse::<toml::Value>()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => matches!((a.parse::<toml::Table>(), b.parse::<toml::Table>()), (Ok(a), Ok(b)) if a == b),
+    }
+}
+
+/// Merge JSON objects key by key, adding array items that aren't there yet and replacing anything else
+fn merge_json(base: &mut serde_json::Value, patch: serde_json::Value) {
+    match (base, patch) {
+        (serde_json::Value::Object(base), serde_json::Value::Object(patch)) => {
+            for (key, value) in patch {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_json(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (serde_json::Value::Array(base), serde_json::Value::Array(patch)) => append_new(base, patch),
+        (base, patch) => *base = patch,
+    }
+}
+
+/// Merge YAML mappings key by key, adding sequence items that aren't there yet and replacing anything else
+fn merge_yaml(base: &mut serde_yaml_ng::Value, patch: serde_yaml_ng::Value) {
+    match (base, patch) {
+        (serde_yaml_ng::Value::Mapping(base), serde_yaml_ng::Value::Mapping(patch)) => {
+            for (key, value) in patch {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_yaml(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (serde_yaml_ng::Value::Sequence(base), serde_yaml_ng::Value::Sequence(patch)) => append_new(base, patch),
+        (base, patch) => *base = patch,
+    }
+}
+
+/// Add the items of a patch array to the end of an array, skipping any it already has
+fn append_new<T: PartialEq>(base: &mut Vec<T>, patch: Vec<T>) {
+    for item in patch {
+        if !base.contains(&item) {
+            base.push(item);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toml_patches_keep_comments_and_formatting() {
+        let base = "# The package\n[package]\nname = \"demo\"  # its name\n\n[dependencies]\nserde = \"1\"\n";
+        let patch = "[dependencies]\nregex = \"1\"\n\n[lints.rust]\nunsafe_code = \"forbid\"\n";
+        let merged = merge_file(Path::new("Cargo.toml"), base, patch).unwrap();
+        assert_eq!(
+            merged,
+            "# The package\n[package]\nname = \"demo\"  # its name\n\n[dependencies]\nserde = \"1\"\nregex = \"1\"\n\n[lints.rust]\nunsafe_code = \"forbid\"\n"
+        );
+    }
+
+    #[test]
+    fn toml_arrays_only_gain_new_items() {
+        let base = "[tool]\nmembers = [\"a\", \"b\"]  # kept\n\n[[bin]]\nname = \"a\"\n";
+        let patch = "[tool]\nmembers = [ \"b\", \"c\" ]\n\n[[bin]]\nname = \"a\"\n\n[[bin]]\nname = \"b\"\n";
+        let merged = merge_file(Path::new("x.toml"), base, patch).unwrap();
+        assert_eq!(merged, "[tool]\nmembers = [\"a\", \"b\", \"c\"]  # kept\n\n[[bin]]\nname = \"a\"\n\n[[bin]]\nname = \"b\"\n");
+    }
+
+    #[test]
+    fn toml_values_replace_what_is_there() {
+        let merged = merge_file(Path::new("x.toml"), "edition = \"2021\" # old\nlib = { path = \"a\" }\n", "edition = \"2024\"\nlib = \"b\"\n").unwrap();
+        assert_eq!(merged, "edition = \"2024\" # old\nlib = \"b\"\n");
+    }
+}
@@ -0,0 +1,86 @@
+use std::env;
+use std::path;
+use std::path::{Component, Path, PathBuf};
+
+/// The name of the folder this app keeps its files in
+const APP_FOLDER: &str = "project-creation";
+
+/// Get the folder the app's configuration lives in
+///
+/// Uses `$XDG_CONFIG_HOME` or `~/.config` on Unix and `%APPDATA%` on Windows
+pub fn config_dir() -> Option<PathBuf> {
+    let base = if cfg!(target_os = "windows") {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else {
+        env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| home_dir().map(|home| home.join(".config")))
+    };
+    base.map(|base| base.join(APP_FOLDER))
+}
+
+/// Get the folder user templates are discovered in
+pub fn templates_dir() -> Option<PathBuf> {
+    config_dir().map(|config| config.join("templates"))
+}
+
+/// Get the folder the app caches things it can fetch again in, like cloned templates
+///
+/// Uses `$XDG_CACHE_HOME` or `~/.cache` on Unix and `%LOCALAPPDATA%` on Windows
+pub fn cache_dir() -> Option<PathBuf> {
+    let base = if cfg!(target_os = "windows") {
+        env::var_os("LOCALAPPDATA").map(PathBuf::from)
+    } else {
+        env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| home_dir().map(|home| home.join(".cache")))
+    };
+    base.map(|base| base.join(APP_FOLDER))
+}
+
+/// Get the current user's home folder
+pub fn home_dir() -> Option<PathBuf> {
+    if cfg!(target_os = "windows") {
+        env::var_os("USERPROFILE").map(PathBuf::from)
+    } else {
+        env::var_os("HOME").map(PathBuf::from)
+    }
+}
+
+/// Get the full path of a folder that may not exist yet, so different ways of writing it give the same path
+///
+/// `.` and `..` are taken out, and the part of the path that exists is canonicalised to follow symlinks.
+pub fn absolute(dir: &Path) -> PathBuf {
+    let mut absolute = PathBuf::new();
+    for component in path::absolute(dir).unwrap_or_else(|_| dir.to_path_buf()).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                absolute.pop();
+            }
+            component => absolute.push(component),
+        }
+    }
+    for existing in absolute.ancestors() {
+        if let Ok(canonical) = existing.canonicalize() {
+            return canonical.join(absolute.strip_prefix(existing).unwrap_or(Path::new("")));
+        }
+    }
+    absolute
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_same_folder_written_differently_is_the_same_path() {
+        let here = env::current_dir().unwrap().canonicalize().unwrap();
+        let missing = here.join("no-such-folder");
+        assert_eq!(absolute(Path::new("no-such-folder")), missing);
+        assert_eq!(absolute(Path::new("./no-such-folder/")), missing);
+        assert_eq!(absolute(&here.join("src/../no-such-folder")), missing);
+        assert_eq!(absolute(Path::new("no-such-folder/deeper/..")), missing);
+        assert_eq!(absolute(Path::new("src")), here.join("src"));
+    }
+}
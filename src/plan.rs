@@ -0,0 +1,311 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use serde::Serialize;
+
+use crate::error::AppError;
+use crate::steps::PostCreateStep;
+
+/// A single file that will be written when the plan is applied
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct PlannedFile {
+    /// The path of the file, relative to the project folder
+    pub path: PathBuf,
+    /// The contents of the file
+    pub contents: String,
+    /// Whether the file should be made executable, only used on Unix
+    pub executable: bool,
+}
+
+/// An external command that will be run inside the project folder
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct PlannedCommand {
+    /// The program to run, looked up on PATH
+    pub program: String,
+    /// The arguments passed to the program
+    pub args: Vec<String>,
+}
+
+impl PlannedCommand {
+    pub fn new(program: &str, args: &[&str]) -> PlannedCommand {
+        PlannedCommand {
+            program: program.to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+        }
+    }
+
+    /// Get the program and its arguments as one list
+    pub fn argv(&self) -> Vec<String> {
+        let mut argv = vec![self.program.clone()];
+        argv.extend(self.args.iter().cloned());
+        argv
+    }
+}
+
+/// A line in the tree view of a plan
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TreeEntry {
+    /// The file or directory name, directories end with a `/`
+    pub name: String,
+    /// How many directories deep the entry is
+    pub depth: usize,
+    /// The index of the file in `Plan::files`, `None` for directories
+    pub file: Option<usize>,
+}
+
+/// Everything that will happen when a project is created
+///
+/// The setup commands are run first so tools like `cargo init` can lay down
+/// their skeleton, then files are written over the top of it, then the post
+/// commands are run.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct Plan {
+    /// The folder the project will be created in
+    pub root: PathBuf,
+    /// The variables the plan was rendered with
+    pub variables: BTreeMap<String, String>,
+    /// The directories to create, relative to the project folder
+    pub directories: Vec<PathBuf>,
+    /// The files to write
+    pub files: Vec<PlannedFile>,
+    /// The commands to run before any files are written, in order
+    pub commands: Vec<PlannedCommand>,
+    /// The commands to run after the files are written, in order
+    pub post_commands: Vec<PlannedCommand>,
+    /// The steps run once the project has been created, with their output shown
+    pub post_create: Vec<PostCreateStep>,
+    /// The template files and folders left out by the template's rules
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub skipped: Vec<PathBuf>,
+}
+
+impl Plan {
+    pub fn new(root: impl Into<PathBuf>) -> Plan {
+        Plan {
+            root: root.into(),
+            ..Plan::default()
+        }
+    }
+
+    /// Add a file to the plan, replacing any file already planned at the same path
+    pub fn add_file(&mut self, path: impl Into<PathBuf>, contents: impl Into<String>) {
+        let path = path.into();
+        let contents = contents.into();
+        match self.files.iter_mut().find(|file| file.path == path) {
+            Some(file) => file.contents = contents,
+            None => self.files.push(PlannedFile { path, contents, executable: false }),
+        }
+    }
+
+    /// Mark a planned file as executable
+    pub fn mark_executable(&mut self, path: impl AsRef<Path>) {
+        if let Some(file) = self.files.iter_mut().find(|file| file.path == path.as_ref()) {
+            file.executable = true;
+        }
+    }
+
+    /// Remove a file from the plan, returning whether it was planned
+    pub fn remove_file(&mut self, path: &Path) -> bool {
+        let before = self.files.len();
+        self.files.retain(|file| file.path != path);
+        self.files.len() != before
+    }
+
+    /// Add a directory to the plan
+    pub fn add_directory(&mut self, path: impl Into<PathBuf>) {
+        let path = path.into();
+        if !self.directories.contains(&path) {
+            self.directories.push(path);
+        }
+    }
+
+    /// Add a setup command to the plan
+    pub fn add_command(&mut self, command: PlannedCommand) {
+        self.commands.push(command);
+    }
+
+    /// Add a command to be run once the files have been written
+    pub fn add_post_command(&mut self, command: PlannedCommand) {
+        self.post_commands.push(command);
+    }
+
+    /// Get every planned file and directory as a sorted tree
+    pub fn tree(&self) -> Vec<TreeEntry> {
+        let mut directories: BTreeSet<PathBuf> = self.directories.iter().cloned().collect();
+        for file in &self.files {
+            let mut parent = file.path.parent();
+            while let Some(dir) = parent.filter(|dir| !dir.as_os_str().is_empty()) {
+                directories.insert(dir.to_path_buf());
+                parent = dir.parent();
+            }
+        }
+
+        // Sorting by path puts every directory straight before its contents
+        let mut paths: Vec<(PathBuf, Option<usize>)> = directories.into_iter().map(|dir| (dir, None)).collect();
+        paths.extend(self.files.iter().enumerate().map(|(index, file)| (file.path.clone(), Some(index))));
+        paths.sort_by(|a, b| a.0.cmp(&b.0));
+
+        paths
+            .into_iter()
+            .map(|(path, file)| {
+                let name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+                TreeEntry {
+                    name: if file.is_none() { format!("{}/", name) } else { name },
+                    depth: path.components().count().saturating_sub(1),
+                    file,
+                }
+            })
+            .collect()
+    }
+
+    /// Describe everything the plan will do, followed by the contents of every file
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        let _ = writeln!(text, "Project folder: {}", self.root.display());
+
+        let sections = [("Commands", &self.commands), ("Post commands", &self.post_commands)];
+        for (title, commands) in sections {
+            if !commands.is_empty() {
+                let _ = writeln!(text, "\n{}:", title);
+                for command in commands {
+                    let _ = writeln!(text, "  $ {}", display_command(&command.argv()));
+                }
+            }
+        }
+
+        let _ = writeln!(text, "\nFiles:");
+        for entry in self.tree() {
+            let _ = writeln!(text, "  {}{}", "  ".repeat(entry.depth), entry.name);
+        }
+        if !self.skipped.is_empty() {
+            let _ = writeln!(text, "\nSkipped by the template's rules:");
+            for path in &self.skipped {
+                let _ = writeln!(text, "  {}", path.display());
+            }
+        }
+
+        if !self.post_create.is_empty() {
+            let _ = writeln!(text, "\nPost create steps:");
+            for step in &self.post_create {
+                let optional = if step.required { "" } else { " (optional)" };
+                let _ = writeln!(text, "  $ {}{}", display_command(&step.command), optional);
+            }
+        }
+
+        for file in &self.files {
+            let _ = writeln!(text, "\n==> {} <==", file.path.display());
+            text.push_str(&file.contents);
+            if !file.contents.ends_with('\n') {
+                text.push('\n');
+            }
+        }
+        text
+    }
+
+    /// Check that every planned file and directory stays inside the project folder
+    pub fn check_paths(&self) -> Result<(), AppError> {
+        let paths = self.directories.iter().chain(self.files.iter().map(|file| &file.path));
+        for path in paths {
+            check_path(path).map_err(AppError::Validation)?;
+        }
+        Ok(())
+    }
+
+    /// Write the plan to disk
+    ///
+    /// The project folder must be missing or empty, so nothing the user has is overwritten.
+    pub fn apply(&self) -> Result<(), AppError> {
+        self.check_paths()?;
+        let not_empty = fs::read_dir(&self.root).map(|mut dir| dir.next().is_some());
+        match not_empty {
+            Ok(true) => return Err(AppError::Validation(format!("{} already exists and isn't empty", self.root.display()))),
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(AppError::Io(self.root.clone(), e)),
+            _ => {}
+        }
+        fs::create_dir_all(&self.root).map_err(AppError::io(&self.root))?;
+
+        self.run_commands(&self.commands)?;
+        self.write_files()?;
+        self.run_commands(&self.post_commands)
+    }
+
+    /// Run each command in the project folder, stopping at the first failure
+    ///
+    /// What the commands print is kept so it can be shown if one fails.
+    fn run_commands(&self, commands: &[PlannedCommand]) -> Result<(), AppError> {
+        for command in commands {
+            let output = Command::new(&command.program)
+                .args(&command.args)
+                .current_dir(&self.root)
+                .stdin(Stdio::null())
+                .output();
+
+            match output {
+                Ok(output) if output.status.success() => {}
+                Ok(output) => {
+                    let printed = format!("{}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+                    return Err(AppError::tool_failed(display_command(&command.argv()), output.status.code(), &printed));
+                }
+                Err(e) if e.kind() == io::ErrorKind::NotFound => return Err(AppError::ToolMissing(command.program.clone())),
+                Err(e) => return Err(AppError::Io(PathBuf::from(&command.program), e)),
+            }
+        }
+        Ok(())
+    }
+
+    /// Create the planned directories and write the planned files
+    pub fn write_files(&self) -> Result<(), AppError> {
+        self.check_paths()?;
+        for directory in &self.directories {
+            let path = self.root.join(directory);
+            fs::create_dir_all(&path).map_err(AppError::io(&path))?;
+        }
+        for file in &self.files {
+            let path = self.root.join(&file.path);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(AppError::io(parent))?;
+            }
+            fs::write(&path, &file.contents).map_err(AppError::io(&path))?;
+            #[cfg(unix)]
+            if file.executable {
+                use std::os::unix::fs::PermissionsExt;
+                fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).map_err(AppError::io(&path))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Check that a path is relative to the project folder and can't climb out of it
+///
+/// Template paths, hook scripts and project records can all name files, so an absolute
+/// path, a drive prefix or a `..` would let them write anywhere on the disk.
+pub fn check_path(path: &Path) -> Result<(), String> {
+    if path.as_os_str().is_empty() {
+        return Err("a planned path is empty".to_string());
+    }
+    let escapes = path.components().any(|component| matches!(component, Component::ParentDir | Component::RootDir | Component::Prefix(_)));
+    if escapes {
+        return Err(format!("`{}` is outside the project folder, paths must be relative and can't use `..`", path.display()));
+    }
+    Ok(())
+}
+
+/// Show a command the way it would be typed into a shell
+pub fn display_command(argv: &[String]) -> String {
+    argv.iter().map(|arg| shell_quote(arg)).collect::<Vec<_>>().join(" ")
+}
+
+/// Quote an argument for a POSIX shell, leaving it bare if it doesn't need quoting
+pub fn shell_quote(arg: &str) -> String {
+    let plain = !arg.is_empty() && arg.chars().all(|c| c.is_ascii_alphanumeric() || "-_./=:@+,%".contains(c));
+    if plain {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
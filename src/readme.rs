@@ -0,0 +1,239 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+
+use serde::{Deserialize, Serialize};
+
+use crate::app::ProjectTypes;
+use crate::ci::CiTarget;
+use crate::error::AppError;
+use crate::licence::Licence;
+use crate::metadata::Metadata;
+use crate::paths;
+use crate::plan::Plan;
+use crate::template::{self, TemplateError};
+
+/// The file in the config folder that replaces the built in README layout
+pub const README_TEMPLATE: &str = "readme.md";
+
+/// The layout of the README when the config folder doesn't have its own,
+/// sections that are turned off or don't apply are empty
+const DEFAULT_TEMPLATE: &str = "# {{ project_name }}
+
+{{ readme_badges }}
+
+{{ project_description }}
+
+{{ readme_install }}
+
+{{ readme_usage }}
+
+{{ readme_tests }}
+
+{{ readme_contributing }}
+
+{{ readme_licence }}
+";
+
+/// The sections of the README that can be turned on and off on the features screen
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ReadmeOptions {
+    /// Badges for the CI status and licence
+    pub badges: bool,
+    /// How to install the project's dependencies or the project itself
+    pub install: bool,
+    /// How to build and run the project
+    pub usage: bool,
+    /// How to run the tests
+    pub tests: bool,
+    /// How to contribute changes
+    pub contributing: bool,
+    /// Which licence the project is under
+    pub licence: bool,
+}
+
+impl Default for ReadmeOptions {
+    fn default() -> ReadmeOptions {
+        ReadmeOptions { badges: false, install: true, usage: true, tests: true, contributing: false, licence: true }
+    }
+}
+
+/// A section of the README, to tick on the features screen
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ReadmeSection {
+    Badges,
+    Install,
+    Usage,
+    Tests,
+    Contributing,
+    Licence,
+}
+
+impl ReadmeSection {
+    pub const ALL: [ReadmeSection; 6] = [
+        ReadmeSection::Badges,
+        ReadmeSection::Install,
+        ReadmeSection::Usage,
+        ReadmeSection::Tests,
+        ReadmeSection::Contributing,
+        ReadmeSection::Licence,
+    ];
+
+    /// Get the name and description of the section
+    pub fn info(self) -> (&'static str, &'static str) {
+        match self {
+            ReadmeSection::Badges => ("README: Badges", "CI status and licence badges, the CI badge needs GitHub Actions and a GitHub repository URL"),
+            ReadmeSection::Install => ("README: Install", "How to set up the project's dependencies or install it"),
+            ReadmeSection::Usage => ("README: Build and Run", "The commands to build and run the project"),
+            ReadmeSection::Tests => ("README: Tests", "The command to run the tests"),
+            ReadmeSection::Contributing => ("README: Contributing", "How to send changes, running the tests first"),
+            ReadmeSection::Licence => ("README: License", "Which licence the project is under, needs a licence on the options screen"),
+        }
+    }
+
+    /// Whether the section is turned on
+    pub fn get(self, options: &ReadmeOptions) -> bool {
+        match self {
+            ReadmeSection::Badges => options.badges,
+            ReadmeSection::Install => options.install,
+            ReadmeSection::Usage => options.usage,
+            ReadmeSection::Tests => options.tests,
+            ReadmeSection::Contributing => options.contributing,
+            ReadmeSection::Licence => options.licence,
+        }
+    }
+
+    /// Turn the section on or off
+    pub fn set(self, options: &mut ReadmeOptions, on: bool) {
+        match self {
+            ReadmeSection::Badges => options.badges = on,
+            ReadmeSection::Install => options.install = on,
+            ReadmeSection::Usage => options.usage = on,
+            ReadmeSection::Tests => options.tests = on,
+            ReadmeSection::Contributing => options.contributing = on,
+            ReadmeSection::Licence => options.licence = on,
+        }
+    }
+}
+
+/// Whether a README can be generated for a project type, templates write their own
+pub fn supports(project_type: ProjectTypes) -> bool {
+    !matches!(project_type, ProjectTypes::Template(_))
+}
+
+/// Add the README to a plan, rendering the user's `readme.md` from the config folder if they have one
+///
+/// Every section is given to the layout as a variable, like `{{ readme_usage }}`,
+/// which is empty when the section is turned off.
+/// ### Parameters
+///  - plan: The plan to add the README to, its variables are given to the layout
+///  - project_type: The type of project, for the commands in the sections
+///  - metadata: The project's details, for the badges
+///  - ci: The CI the project has, for its badge
+///  - licence: The licence the project has
+///  - options: The sections to write
+pub fn add_readme(
+    plan: &mut Plan,
+    project_type: ProjectTypes,
+    metadata: &Metadata,
+    ci: CiTarget,
+    licence: Licence,
+    options: &ReadmeOptions,
+) -> Result<(), AppError> {
+    if !supports(project_type) {
+        return Ok(());
+    }
+    let custom = paths::config_dir().map(|dir| dir.join(README_TEMPLATE)).unwrap_or_default();
+    let layout = match fs::read_to_string(&custom) {
+        Ok(text) => text,
+        Err(e) if e.kind() != io::ErrorKind::NotFound && !custom.as_os_str().is_empty() => return Err(AppError::Io(custom, e)),
+        Err(_) => DEFAULT_TEMPLATE.to_string(),
+    };
+
+    let name = plan.variables.get("project_name").cloned().unwrap_or_default();
+    let commands = Commands::for_type(project_type, &name);
+    let mut sections = BTreeMap::new();
+    sections.insert("readme_badges", if options.badges { badges(metadata, ci, licence) } else { String::new() });
+    sections.insert("readme_install", section(options.install, "Install", &commands.install));
+    sections.insert("readme_usage", section(options.usage, "Build and Run", &commands.usage));
+    sections.insert("readme_tests", section(options.tests, "Tests", &commands.tests));
+    let contributing = format!(
+        "## Contributing\n\nPull requests are welcome. Please open an issue first to talk about bigger changes, and make sure the tests pass before sending a pull request:\n\n```sh\n{}\n```",
+        commands.tests.join("\n")
+    );
+    sections.insert("readme_contributing", if options.contributing { contributing } else { String::new() });
+    let licence_section = format!("## License\n\nThis project is licensed under the {} license, see [LICENSE](LICENSE) for the details.", licence);
+    sections.insert("readme_licence", if options.licence && licence != Licence::None { licence_section } else { String::new() });
+
+    let mut variables = plan.variables.clone();
+    variables.extend(sections.into_iter().map(|(name, text)| (name.to_string(), text)));
+    let mut text = template::render(&layout, &variables)
+        .map_err(|(line, message)| AppError::from(TemplateError::Render { file: custom, line, message }))?
+        .trim()
+        .to_string();
+    // Sections that are turned off leave blank lines behind
+    while text.contains("\n\n\n") {
+        text = text.replace("\n\n\n", "\n\n");
+    }
+    plan.add_file("README.md", text + "\n");
+    Ok(())
+}
+
+/// The commands shown in the README for a project type
+struct Commands {
+    install: Vec<String>,
+    usage: Vec<String>,
+    tests: Vec<String>,
+}
+
+impl Commands {
+    fn for_type(project_type: ProjectTypes, project_name: &str) -> Commands {
+        let lines = |commands: &[&str]| commands.iter().map(|command| command.to_string()).collect::<Vec<_>>();
+        match project_type {
+            ProjectTypes::Python => Commands {
+                install: lines(&["python3 -m venv .venv", ". .venv/bin/activate"]),
+                usage: lines(&["python src/main.py"]),
+                tests: lines(&["python -m unittest"]),
+            },
+            ProjectTypes::UvPython => Commands {
+                install: lines(&["uv sync"]),
+                usage: lines(&["uv run main.py"]),
+                tests: lines(&["uv run python -m unittest"]),
+            },
+            ProjectTypes::Rust => Commands {
+                install: lines(&["cargo install --path ."]),
+                usage: lines(&["cargo build --release", "cargo run"]),
+                tests: lines(&["cargo test"]),
+            },
+            ProjectTypes::CmakeCpp => Commands {
+                install: lines(&["cmake -S . -B build", "cmake --build build", "cmake --install build"]),
+                usage: vec!["cmake -S . -B build".to_string(), "cmake --build build".to_string(), format!("./build/{}", project_name)],
+                tests: lines(&["cmake --build build", "ctest --test-dir build"]),
+            },
+            ProjectTypes::Template(_) => Commands { install: Vec::new(), usage: Vec::new(), tests: Vec::new() },
+        }
+    }
+}
+
+/// Render a section with a heading and its commands, or nothing if it is turned off
+fn section(on: bool, heading: &str, commands: &[String]) -> String {
+    if !on || commands.is_empty() {
+        return String::new();
+    }
+    format!("## {}\n\n```sh\n{}\n```", heading, commands.join("\n"))
+}
+
+/// Render the badges that apply to the project on one line
+fn badges(metadata: &Metadata, ci: CiTarget, licence: Licence) -> String {
+    let mut badges = Vec::new();
+    let github = metadata.repository.strip_prefix("https://github.com/").map(|path| path.trim_end_matches('/').trim_end_matches(".git"));
+    if let (CiTarget::GitHubActions, Some(path)) = (ci, github) {
+        badges.push(format!("[![CI](https://github.com/{}/actions/workflows/ci.yml/badge.svg)](https://github.com/{}/actions/workflows/ci.yml)", path, path));
+    }
+    if licence != Licence::None {
+        let escaped = licence.to_string().replace('-', "--");
+        badges.push(format!("[![License: {}](https://img.shields.io/badge/license-{}-blue.svg)](LICENSE)", licence, escaped));
+    }
+    badges.join(" ")
+}
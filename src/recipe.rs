@@ -0,0 +1,72 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::app::{ProjectOptions, ProjectTypes};
+use crate::error::AppError;
+use crate::metadata::Metadata;
+
+/// Every choice made in the wizard, saved so the same project can be created again
+///
+/// Every field is optional so a recipe can also just pre-fill part of the wizard.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Recipe {
+    /// A built in project type: `python`, `uv-python`, `rust` or `cmake-cpp`
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub project_type: Option<String>,
+    /// The name of a user template or a git repository to fetch one from, used instead of `type`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    /// The name of the project
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// The folder to create the project in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub folder: Option<String>,
+    /// The choices from the options screen
+    pub options: ProjectOptions,
+    /// The description, authors and other details of the project
+    #[serde(skip_serializing_if = "Metadata::is_empty")]
+    pub metadata: Metadata,
+    /// The names of the features added on top of the project
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub features: Vec<String>,
+    /// Extra variables given to templates
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub variables: BTreeMap<String, String>,
+}
+
+impl Recipe {
+    /// Read a recipe from a TOML file
+    pub fn load(path: &Path) -> Result<Recipe, AppError> {
+        let text = fs::read_to_string(path).map_err(AppError::io(path))?;
+        toml::from_str(&text).map_err(|e| AppError::ConfigParse(path.to_path_buf(), e.message().to_string()))
+    }
+
+    /// Write the recipe to a TOML file
+    pub fn save(&self, path: &Path) -> Result<(), AppError> {
+        let text = toml::to_string(self).map_err(|e| AppError::Validation(e.to_string()))?;
+        fs::write(path, text).map_err(AppError::io(path))
+    }
+}
+
+/// Get the name a built in project type has in a recipe
+pub fn type_key(project_type: ProjectTypes) -> Option<&'static str> {
+    match project_type {
+        ProjectTypes::Python => Some("python"),
+        ProjectTypes::UvPython => Some("uv-python"),
+        ProjectTypes::Rust => Some("rust"),
+        ProjectTypes::CmakeCpp => Some("cmake-cpp"),
+        ProjectTypes::Template(_) => None,
+    }
+}
+
+/// Get the built in project type with the given recipe name
+pub fn type_from_key(key: &str) -> Option<ProjectTypes> {
+    [ProjectTypes::Python, ProjectTypes::UvPython, ProjectTypes::Rust, ProjectTypes::CmakeCpp]
+        .into_iter()
+        .find(|project_type| type_key(*project_type) == Some(key))
+}
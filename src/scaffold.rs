@@ -0,0 +1,56 @@
+use std::path::Path;
+
+use crate::app::ProjectTypes;
+use crate::plan::Plan;
+
+/// Whether a test scaffold can be generated for a project type
+pub fn supports(project_type: ProjectTypes) -> bool {
+    !matches!(project_type, ProjectTypes::Template(_))
+}
+
+/// Add a first passing test and anything needed to run it to a plan
+/// ### Parameters
+///  - plan: The plan to add the files to
+///  - project_type: The type of project the tests are for
+///  - project_name: The name of the project, used to find its binary
+pub fn add_tests(plan: &mut Plan, project_type: ProjectTypes, project_name: &str) {
+    match project_type {
+        ProjectTypes::Python => {
+            plan.add_file("tests/__init__.py", "");
+            plan.add_file("tests/test_main.py", python_test("src.main", "Hello, World!"));
+        }
+        ProjectTypes::UvPython => {
+            plan.add_file("tests/__init__.py", "");
+            plan.add_file("tests/test_main.py", python_test("main", &format!("Hello from {}!", project_name)));
+        }
+        ProjectTypes::Rust => {
+            plan.add_file(
+                "tests/cli.rs",
+                format!(
+                    "use std::process::Command;\n\n#[test]\nfn prints_hello() {{\n    let output = Command::new(env!(\"CARGO_BIN_EXE_{}\")).output().expect(\"the binary should run\");\n    assert!(output.status.success());\n    assert_eq!(String::from_utf8_lossy(&output.stdout), \"Hello, world!\\n\");\n}}\n",
+                    project_name
+                ),
+            );
+        }
+        ProjectTypes::CmakeCpp => {
+            plan.add_file(
+                "tests/main_test.cpp",
+                "#include <cstdlib>\n#include <iostream>\n\nint main() {\n    if (1 + 1 != 2) {\n        std::cerr << \"1 + 1 should be 2\" << std::endl;\n        return EXIT_FAILURE;\n    }\n    return EXIT_SUCCESS;\n}\n",
+            );
+            if let Some(file) = plan.files.iter_mut().find(|file| file.path == Path::new("CMakeLists.txt")) {
+                file.contents.push_str(
+                    "\nenable_testing()\nadd_executable(${PROJECT_NAME}_tests tests/main_test.cpp)\nadd_test(NAME ${PROJECT_NAME}_tests COMMAND ${PROJECT_NAME}_tests)\n",
+                );
+            }
+        }
+        ProjectTypes::Template(_) => {}
+    }
+}
+
+/// Render a unittest case that runs `main` and checks what it prints, which pytest runs too
+fn python_test(module: &str, expected: &str) -> String {
+    format!(
+        "import io\nimport unittest\nfrom contextlib import redirect_stdout\n\nfrom {} import main\n\n\nclass MainTest(unittest.TestCase):\n    def test_prints_greeting(self):\n        output = io.StringIO()\n        with redirect_stdout(output):\n            main()\n        self.assertEqual(output.getvalue(), \"{}\\n\")\n\n\nif __name__ == \"__main__\":\n    unittest.main()\n",
+        module, expected
+    )
+}
@@ -0,0 +1,179 @@
+use std::fmt::Write as _;
+
+use crate::plan::{shell_quote, Plan};
+
+/// The kinds of script a plan can be exported as
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScriptKind {
+    Bash,
+    PowerShell,
+}
+
+impl ScriptKind {
+    /// Get the script kind with the given name
+    pub fn from_name(name: &str) -> Option<ScriptKind> {
+        match name {
+            "bash" | "sh" => Some(ScriptKind::Bash),
+            "powershell" | "pwsh" | "ps1" => Some(ScriptKind::PowerShell),
+            _ => None,
+        }
+    }
+
+    /// Get the script kind that runs on this platform
+    pub fn native() -> ScriptKind {
+        if cfg!(target_os = "windows") { ScriptKind::PowerShell } else { ScriptKind::Bash }
+    }
+
+    /// Get the file extension for this kind of script
+    pub fn extension(self) -> &'static str {
+        match self {
+            ScriptKind::Bash => "sh",
+            ScriptKind::PowerShell => "ps1",
+        }
+    }
+}
+
+/// Export a plan as a self contained script that creates the same project
+pub fn emit(plan: &Plan, kind: ScriptKind) -> String {
+    match kind {
+        ScriptKind::Bash => emit_bash(plan),
+        ScriptKind::PowerShell => emit_powershell(plan),
+    }
+}
+
+/// Export a plan as a bash script
+fn emit_bash(plan: &Plan) -> String {
+    let root = shell_quote(&plan.root.to_string_lossy());
+    let mut script = String::from("#!/usr/bin/env bash\n# Generated by create-project\nset -euo pipefail\n\n");
+    let _ = writeln!(
+        script,
+        "if [ -e {root} ] && [ -n \"$(ls -A {root})\" ]; then\n    echo \"error: \"{root}\" already exists and isn't empty\" >&2\n    exit 1\nfi"
+    );
+    let _ = writeln!(script, "mkdir -p -- {root}\ncd -- {root}");
+
+    let run = |script: &mut String, argv: &[String], required: bool| {
+        let command = argv.iter().map(|arg| shell_quote(arg)).collect::<Vec<_>>().join(" ");
+        if required {
+            let _ = writeln!(script, "{}", command);
+        } else {
+            let _ = writeln!(script, "{} || echo {} >&2", command, shell_quote(&format!("optional step failed: {}", argv.join(" "))));
+        }
+    };
+
+    if !plan.commands.is_empty() {
+        script.push_str("\n# Setup commands\n");
+        for command in &plan.commands {
+            run(&mut script, &command.argv(), true);
+        }
+    }
+
+    if !plan.directories.is_empty() || !plan.files.is_empty() {
+        script.push_str("\n# Files\n");
+    }
+    for directory in &plan.directories {
+        let _ = writeln!(script, "mkdir -p -- {}", shell_quote(&directory.to_string_lossy()));
+    }
+    for file in &plan.files {
+        let path = file.path.to_string_lossy().replace('\\', "/");
+        if let Some(parent) = file.path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            let _ = writeln!(script, "mkdir -p -- {}", shell_quote(&parent.to_string_lossy().replace('\\', "/")));
+        }
+        if file.contents.ends_with('\n') {
+            // A quoted delimiter stops the shell expanding anything in the contents
+            let delimiter = heredoc_delimiter(&file.contents);
+            let _ = write!(script, "cat > {} <<'{}'\n{}{}\n", shell_quote(&path), delimiter, file.contents, delimiter);
+        } else {
+            let _ = writeln!(script, "printf '%s' {} > {}", shell_quote(&file.contents), shell_quote(&path));
+        }
+        if file.executable {
+            let _ = writeln!(script, "chmod +x {}", shell_quote(&path));
+        }
+    }
+
+    if !plan.post_commands.is_empty() || !plan.post_create.is_empty() {
+        script.push_str("\n# Post create commands\n");
+    }
+    for command in &plan.post_commands {
+        run(&mut script, &command.argv(), true);
+    }
+    for step in &plan.post_create {
+        run(&mut script, &step.command, step.required);
+    }
+
+    script
+}
+
+/// Pick a heredoc delimiter that doesn't appear as a line in the contents
+fn heredoc_delimiter(contents: &str) -> String {
+    let mut delimiter = String::from("CREATE_PROJECT_EOF");
+    let mut count = 0;
+    while contents.lines().any(|line| line == delimiter) {
+        count += 1;
+        delimiter = format!("CREATE_PROJECT_EOF_{}", count);
+    }
+    delimiter
+}
+
+/// Quote a string for PowerShell, single quoted strings can span lines and only need `'` doubled
+fn powershell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// Export a plan as a PowerShell script
+fn emit_powershell(plan: &Plan) -> String {
+    let root = powershell_quote(&plan.root.to_string_lossy());
+    let mut script = String::from("# Generated by create-project\n$ErrorActionPreference = 'Stop'\n\n");
+    let _ = writeln!(
+        script,
+        "if ((Test-Path -LiteralPath {root}) -and (Get-ChildItem -LiteralPath {root} -Force)) {{\n    Write-Error ({root} + \" already exists and isn't empty\")\n    exit 1\n}}"
+    );
+    let _ = writeln!(script, "New-Item -ItemType Directory -Force -Path {root} | Out-Null\nSet-Location -LiteralPath {root}");
+
+    let run = |script: &mut String, argv: &[String], required: bool| {
+        let command = argv.iter().map(|arg| powershell_quote(arg)).collect::<Vec<_>>().join(" ");
+        let on_failure = if required {
+            "exit $LASTEXITCODE".to_string()
+        } else {
+            format!("Write-Warning {}", powershell_quote(&format!("optional step failed: {}", argv.join(" "))))
+        };
+        let _ = writeln!(script, "& {}\nif ($LASTEXITCODE -ne 0) {{ {} }}", command, on_failure);
+    };
+
+    if !plan.commands.is_empty() {
+        script.push_str("\n# Setup commands\n");
+        for command in &plan.commands {
+            run(&mut script, &command.argv(), true);
+        }
+    }
+
+    if !plan.directories.is_empty() || !plan.files.is_empty() {
+        script.push_str("\n# Files\n");
+    }
+    for directory in &plan.directories {
+        let _ = writeln!(script, "New-Item -ItemType Directory -Force -Path {} | Out-Null", powershell_quote(&directory.to_string_lossy()));
+    }
+    for file in &plan.files {
+        if let Some(parent) = file.path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            let _ = writeln!(script, "New-Item -ItemType Directory -Force -Path {} | Out-Null", powershell_quote(&parent.to_string_lossy()));
+        }
+        // WriteAllText writes UTF-8 without a BOM and keeps the contents exactly as they are
+        let _ = writeln!(
+            script,
+            "[IO.File]::WriteAllText((Join-Path $PWD {}), {})",
+            powershell_quote(&file.path.to_string_lossy()),
+            powershell_quote(&file.contents)
+        );
+    }
+
+    if !plan.post_commands.is_empty() || !plan.post_create.is_empty() {
+        script.push_str("\n# Post create commands\n");
+    }
+    for command in &plan.post_commands {
+        run(&mut script, &command.argv(), true);
+    }
+    for step in &plan.post_create {
+        run(&mut script, &step.command, step.required);
+    }
+
+    script
+}
@@ -0,0 +1,115 @@
+use std::env;
+use std::ffi::OsString;
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+/// Get the user's shell from `$SHELL`, or the platform default
+fn shell_program() -> OsString {
+    match env::var_os("SHELL") {
+        Some(shell) if !shell.is_empty() => shell,
+        _ if cfg!(target_os = "windows") => OsString::from("powershell"),
+        _ => OsString::from("/bin/sh"),
+    }
+}
+
+/// Build the command for an interactive shell in the project folder
+///
+/// If the project has a `.venv` the shell gets the same environment that
+/// sourcing its activate script would give it, so it works in any shell.
+fn shell_command(dir: &Path) -> Command {
+    let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+    let mut command = Command::new(shell_program());
+    command.current_dir(&dir).env("PWD", &dir);
+
+    let venv = dir.join(".venv");
+    if venv.is_dir() {
+        let bin = if cfg!(target_os = "windows") { venv.join("Scripts") } else { venv.join("bin") };
+        let mut paths = vec![bin];
+        if let Some(path) = env::var_os("PATH") {
+            paths.extend(env::split_paths(&path));
+        }
+        if let Ok(path) = env::join_paths(paths) {
+            command.env("PATH", path);
+        }
+        command.env("VIRTUAL_ENV", &venv);
+        command.env_remove("PYTHONHOME");
+    }
+
+    command
+}
+
+/// Replace this process with an interactive shell in the project folder
+///
+/// The TUI must have already given the terminal back. Exiting the shell
+/// returns to whatever started this program, in the folder it was started in.
+/// ### Returns
+///  - Only returns if the shell couldn't be started
+pub fn enter(dir: &Path) -> io::Error {
+    let mut command = shell_command(dir);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.exec()
+    }
+
+    #[cfg(not(unix))]
+    {
+        match command.status() {
+            Ok(status) => std::process::exit(status.code().unwrap_or(0)),
+            Err(e) => e,
+        }
+    }
+}
+
+/// The shells that `create-project init` can print integration for
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShellKind {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl ShellKind {
+    /// Get the shell with the given name
+    pub fn from_name(name: &str) -> Option<ShellKind> {
+        match name {
+            "bash" => Some(ShellKind::Bash),
+            "zsh" => Some(ShellKind::Zsh),
+            "fish" => Some(ShellKind::Fish),
+            _ => None,
+        }
+    }
+
+    /// Get a shell function that wraps `create-project` and cds into the created project
+    pub fn init_script(self) -> &'static str {
+        match self {
+            ShellKind::Bash | ShellKind::Zsh => "\
+create-project() {
+    local cd_file exit_code
+    cd_file=\"$(mktemp)\" || return
+    command create-project --cd-file \"$cd_file\" \"$@\"
+    exit_code=$?
+    if [ -s \"$cd_file\" ]; then
+        cd -- \"$(cat \"$cd_file\")\" || exit_code=$?
+    fi
+    rm -f -- \"$cd_file\"
+    return $exit_code
+}
+",
+            ShellKind::Fish => "\
+function create-project
+    set -l cd_file (mktemp); or return
+    command create-project --cd-file $cd_file $argv
+    set -l code $status
+    if test -s $cd_file
+        cd (cat $cd_file); or set code $status
+    end
+    rm -f -- $cd_file
+    return $code
+end
+",
+        }
+    }
+}
@@ -0,0 +1,323 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::error::AppError;
+use crate::paths;
+use crate::plan::display_command;
+use crate::template::Template;
+use crate::update;
+
+/// The file in the cache folder listing the templates fetched most recently
+const RECENT_FILE: &str = "recent-templates";
+/// How many recent templates are remembered
+const MAX_RECENT: usize = 5;
+
+/// Where to fetch a template from, parsed from `<repo>[#[<ref>][:<subdir>]]`
+///
+/// The repository is a local path or a `file://` URL, to a normal or a bare repository.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TemplateSource {
+    /// The repository to clone
+    pub repo: PathBuf,
+    /// The branch, tag or commit to check out, the repository's default branch if not given
+    pub reference: Option<String>,
+    /// The folder inside the repository holding the template
+    pub subdir: Option<PathBuf>,
+}
+
+impl TemplateSource {
+    /// Parse a template source, only local repositories are supported
+    pub fn parse(spec: &str) -> Result<TemplateSource, String> {
+        let (location, fragment) = match spec.split_once('#') {
+            Some((location, fragment)) => (location, Some(fragment)),
+            None => (spec, None),
+        };
+        let repo = match location.split_once("://") {
+            Some(("file", path)) => PathBuf::from(path),
+            Some((scheme, _)) => return Err(format!("`{}://` templates aren't supported, only local repositories", scheme)),
+            None => PathBuf::from(location),
+        };
+        if repo.as_os_str().is_empty() {
+            return Err(format!("`{}` doesn't name a repository", spec));
+        }
+
+        let (reference, subdir) = match fragment.map(|fragment| fragment.split_once(':').unwrap_or((fragment, ""))) {
+            Some((reference, subdir)) => (
+                (!reference.is_empty()).then(|| reference.to_string()),
+                (!subdir.is_empty()).then(|| PathBuf::from(subdir)),
+            ),
+            None => (None, None),
+        };
+        if subdir.as_ref().is_some_and(|subdir| subdir.is_absolute() || subdir.components().any(|c| c.as_os_str() == "..")) {
+            return Err(format!("the template folder in `{}` must be inside the repository", spec));
+        }
+
+        Ok(TemplateSource { repo, reference, subdir })
+    }
+
+    /// Write the source back out as a spec, in the form `file://<repo>[#[<ref>][:<subdir>]]`
+    pub fn to_spec(&self, repo: &Path) -> String {
+        let mut spec = format!("file://{}", repo.display());
+        if self.reference.is_some() || self.subdir.is_some() {
+            spec.push('#');
+            spec.push_str(self.reference.as_deref().unwrap_or_default());
+        }
+        if let Some(subdir) = &self.subdir {
+            spec.push(':');
+            spec.push_str(&subdir.to_string_lossy().replace('\\', "/"));
+        }
+        spec
+    }
+
+    /// Get the folder this source is cloned into, one per repository and reference
+    ///
+    /// The folder is named with a hash that doesn't change between builds, so clones
+    /// and the recent templates pointing at them survive updating create-project.
+    fn cache_path(&self, repo: &Path) -> Option<PathBuf> {
+        let hash = update::fingerprint(&format!("{}#{}", repo.display(), self.reference.as_deref().unwrap_or_default()));
+        // The clone is named after the repository so templates without a name get a sensible one, a repository at `/`
+        // or ending in `..` has no name of its own
+        let name = repo
+            .file_name()
+            .map(|name| name.to_string_lossy().trim_end_matches(".git").to_string())
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| "template".to_string());
+        Some(paths::cache_dir()?.join("templates").join(hash).join(name))
+    }
+}
+
+/// Clone or update a template from a local repository and load it
+///
+/// A cache of the clone is kept so fetching the same template again only
+/// fetches what has changed. Successful fetches are added to the recent templates.
+pub fn fetch(spec: &str) -> Result<Template, AppError> {
+    let spec = normalise(spec)?;
+    let template = checkout(&spec, TemplateSource::parse(&spec).map_err(AppError::Validation)?)?;
+    remember(&spec);
+    Ok(template)
+}
+
+/// Rewrite a template source so it names the repository by its full path
+///
+/// The spec is saved in the recent templates, recipes and project records, which
+/// are used again from other folders, so a relative path would find the wrong repository.
+pub fn normalise(spec: &str) -> Result<String, AppError> {
+    let source = TemplateSource::parse(spec).map_err(AppError::Validation)?;
+    let repo = source.repo.canonicalize().map_err(|e| {
+        // `repo:dir` looks like it should work, but the folder only comes after the `#`
+        match source.repo.to_string_lossy().rsplit_once(':') {
+            Some((repo, subdir)) if !spec.contains('#') && Path::new(repo).exists() => {
+                AppError::Validation(format!("{} doesn't exist, for the folder `{}` inside {} use `{}#:{}`", source.repo.display(), subdir, repo, repo, subdir))
+            }
+            _ => AppError::Io(source.repo.clone(), e),
+        }
+    })?;
+    Ok(source.to_spec(&repo))
+}
+
+/// Load a template from a local repository as it was at a commit, like when a project was created from it, and use it
+///
+/// The commit is checked out into a worktree of the clone `spec` is fetched into, which
+/// is removed again once `use_template` returns, so looking at old commits doesn't leave
+/// a clone behind for each one. The template isn't added to the recent templates.
+pub fn with_commit<T>(spec: &str, commit: &str, use_template: impl FnOnce(Template) -> Result<T, AppError>) -> Result<T, AppError> {
+    // The commit comes from a project's record, and names a folder and is given to git
+    if commit.is_empty() || !commit.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(AppError::Validation(format!("`{}` isn't a commit", commit)));
+    }
+    let spec = normalise(spec)?;
+    let source = TemplateSource::parse(&spec).map_err(AppError::Validation)?;
+    let repo = source.repo.canonicalize().map_err(AppError::io(&source.repo))?;
+    let clone = update_clone(&source, &repo)?;
+    git(&clone, &["rev-parse", "--verify", "--quiet", &format!("{}^{{commit}}", commit)])
+        .map_err(|_| AppError::Validation(format!("{} has no commit `{}`", repo.display(), commit)))?;
+
+    // The worktree's folder has the clone's name, which names templates without one
+    let parent = clone.with_file_name(format!(".commit-{}", commit));
+    let worktree = parent.join(clone.file_name().unwrap_or_default());
+    let remove = || {
+        let _ = fs::remove_dir_all(&parent);
+        let _ = git(&clone, &["worktree", "prune"]);
+    };
+    // A worktree left behind by an update that was killed would stop it being added again
+    remove();
+    git(&clone, &["worktree", "add", "--quiet", "--detach", &worktree.to_string_lossy(), commit])?;
+
+    let root = match &source.subdir {
+        Some(subdir) => worktree.join(subdir),
+        None => worktree.clone(),
+    };
+    let result = Template::load(&root).map_err(AppError::from).and_then(|mut template| {
+        template.source = Some(spec.clone());
+        template.commit = Some(commit.to_string());
+        use_template(template)
+    });
+    remove();
+    result
+}
+
+/// Clone or update the repository of a template source and check out the template in it
+fn checkout(spec: &str, source: TemplateSource) -> Result<Template, AppError> {
+    let repo = source.repo.canonicalize().map_err(AppError::io(&source.repo))?;
+    let clone = update_clone(&source, &repo)?;
+
+    // Branches are taken from the remote so they follow the repository after the first fetch
+    let commit = match &source.reference {
+        Some(reference) => git(&clone, &["rev-parse", "--verify", "--quiet", &format!("origin/{}^{{commit}}", reference)])
+            .or_else(|_| git(&clone, &["rev-parse", "--verify", "--quiet", &format!("{}^{{commit}}", reference)]))
+            .map_err(|_| AppError::Validation(format!("{} has no branch, tag or commit `{}`", repo.display(), reference)))?,
+        None => git(&clone, &["rev-parse", "--verify", "--quiet", "origin/HEAD^{commit}"])
+            .map_err(|_| AppError::Validation(format!("{} has no default branch, give one with `#<branch>`", repo.display())))?,
+    };
+    git(&clone, &["checkout", "--quiet", "--force", "--detach", commit.trim()])?;
+
+    let root = match &source.subdir {
+        Some(subdir) => clone.join(subdir),
+        None => clone,
+    };
+    let mut template = Template::load(&root)?;
+    template.source = Some(spec.to_string());
+    template.commit = Some(commit.trim().to_string());
+    Ok(template)
+}
+
+/// Clone the repository of a template source into the cache, or fetch what has changed if it already is
+/// ### Returns
+///  - The folder of the clone
+fn update_clone(source: &TemplateSource, repo: &Path) -> Result<PathBuf, AppError> {
+    let clone = source.cache_path(repo).ok_or_else(|| {
+        AppError::Io(repo.to_path_buf(), io::Error::new(io::ErrorKind::NotFound, "couldn't find a cache folder for templates"))
+    })?;
+
+    if clone.join(".git").is_dir() {
+        git(&clone, &["fetch", "--quiet", "--force", "--tags", "origin"])?;
+    } else {
+        if let Some(parent) = clone.parent() {
+            fs::create_dir_all(parent).map_err(AppError::io(parent))?;
+        }
+        let _ = fs::remove_dir_all(&clone);
+        let cwd = clone.parent().unwrap_or(Path::new("."));
+        git(cwd, &["clone", "--quiet", "--no-checkout", &repo.to_string_lossy(), &clone.to_string_lossy()])?;
+    }
+
+    Ok(clone)
+}
+
+/// Load the templates fetched most recently from their cached clones, without fetching them again
+pub fn recent() -> Vec<Template> {
+    recent_specs()
+        .into_iter()
+        .filter_map(|spec| {
+            let source = TemplateSource::parse(&spec).ok()?;
+            let repo = source.repo.canonicalize().ok()?;
+            let clone = source.cache_path(&repo)?;
+            let root = match &source.subdir {
+                Some(subdir) => clone.join(subdir),
+                None => clone,
+            };
+            let mut template = Template::load(&root).ok()?;
+            template.commit = git(&root, &["rev-parse", "HEAD"]).ok().map(|commit| commit.trim().to_string());
+            template.source = Some(source.to_spec(&repo));
+            Some(template)
+        })
+        .collect()
+}
+
+/// Get the sources of the recent templates, most recent first
+fn recent_specs() -> Vec<String> {
+    paths::cache_dir()
+        .and_then(|dir| fs::read_to_string(dir.join(RECENT_FILE)).ok())
+        .map(|text| text.lines().filter(|line| !line.is_empty()).map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+/// Move a template source to the top of the recent templates
+fn remember(spec: &str) {
+    let Some(dir) = paths::cache_dir() else {
+        return;
+    };
+    let mut specs = recent_specs();
+    specs.retain(|recent| recent != spec);
+    specs.insert(0, spec.to_string());
+    specs.truncate(MAX_RECENT);
+    let _ = fs::create_dir_all(&dir);
+    let _ = fs::write(dir.join(RECENT_FILE), specs.join("\n") + "\n");
+}
+
+/// Run git in a folder, returning what it printed
+fn git(dir: &Path, args: &[&str]) -> Result<String, AppError> {
+    let output = Command::new("git").args(args).current_dir(dir).stdin(Stdio::null()).output().map_err(|e| match e.kind() {
+        io::ErrorKind::NotFound => AppError::ToolMissing("git".to_string()),
+        _ => AppError::Io(PathBuf::from("git"), e),
+    })?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        let argv: Vec<String> = ["git"].iter().chain(args).map(|arg| arg.to_string()).collect();
+        Err(AppError::tool_failed(display_command(&argv), output.status.code(), &String::from_utf8_lossy(&output.stderr)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(repo: &str, reference: Option<&str>, subdir: Option<&str>) -> TemplateSource {
+        TemplateSource { repo: PathBuf::from(repo), reference: reference.map(str::to_string), subdir: subdir.map(PathBuf::from) }
+    }
+
+    #[test]
+    fn parses_every_part() {
+        assert_eq!(TemplateSource::parse("/srv/repo"), Ok(source("/srv/repo", None, None)));
+        assert_eq!(TemplateSource::parse("/srv/repo#v2"), Ok(source("/srv/repo", Some("v2"), None)));
+        assert_eq!(TemplateSource::parse("/srv/repo#v2:python"), Ok(source("/srv/repo", Some("v2"), Some("python"))));
+        assert_eq!(TemplateSource::parse("/srv/repo#:python"), Ok(source("/srv/repo", None, Some("python"))));
+        assert_eq!(TemplateSource::parse("file:///srv/repo.git#main:rust"), Ok(source("/srv/repo.git", Some("main"), Some("rust"))));
+        assert_eq!(TemplateSource::parse("../repo#"), Ok(source("../repo", None, None)));
+    }
+
+    #[test]
+    fn a_folder_without_a_hash_is_part_of_the_repository() {
+        assert_eq!(TemplateSource::parse("/srv/repo:python"), Ok(source("/srv/repo:python", None, None)));
+    }
+
+    #[test]
+    fn rejects_bad_sources() {
+        assert!(TemplateSource::parse("https://example.com/repo").is_err());
+        assert!(TemplateSource::parse("#main").is_err());
+        assert!(TemplateSource::parse("file://").is_err());
+        assert!(TemplateSource::parse("/srv/repo#main:../other").is_err());
+        assert!(TemplateSource::parse("/srv/repo#main:/etc").is_err());
+    }
+
+    #[test]
+    fn specs_parse_back_to_the_same_source() {
+        for spec in ["/srv/repo", "/srv/repo#v2", "/srv/repo#v2:python", "/srv/repo#:python", "file:///srv/repo.git#main:rust/cli"] {
+            let parsed = TemplateSource::parse(spec).unwrap();
+            let written = parsed.to_spec(&parsed.repo);
+            assert!(written.starts_with("file:///srv/"), "{} was written as {}", spec, written);
+            assert_eq!(TemplateSource::parse(&written), Ok(parsed), "{} was written as {}", spec, written);
+        }
+    }
+
+    #[test]
+    fn writes_the_shortest_spec() {
+        assert_eq!(source("/srv/repo", None, None).to_spec(Path::new("/srv/repo")), "file:///srv/repo");
+        assert_eq!(source("repo", Some("v2"), None).to_spec(Path::new("/srv/repo")), "file:///srv/repo#v2");
+        assert_eq!(source("repo", None, Some("python")).to_spec(Path::new("/srv/repo")), "file:///srv/repo#:python");
+        assert_eq!(source("repo", Some("v2"), Some("python")).to_spec(Path::new("/srv/repo")), "file:///srv/repo#v2:python");
+    }
+
+    #[test]
+    fn clones_are_named_after_the_repository() {
+        // There's no cache folder without a home folder, so there's nothing to name
+        let Some(_) = paths::cache_dir() else { return };
+        let name = |repo: &str| source(repo, None, None).cache_path(Path::new(repo)).unwrap().file_name().unwrap().to_owned();
+        assert_eq!(name("/srv/python.git"), "python");
+        assert_eq!(name("/"), "template");
+        assert_eq!(name("/srv/repo/.."), "template");
+        assert_eq!(name("/srv/.git"), "template");
+    }
+}
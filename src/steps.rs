@@ -0,0 +1,147 @@
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// A command to run in a new project once it has been created
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct PostCreateStep {
+    /// The program and its arguments, run without a shell
+    pub command: Vec<String>,
+    /// Whether a failure should stop the steps after it
+    #[serde(default = "default_required")]
+    pub required: bool,
+}
+
+fn default_required() -> bool {
+    true
+}
+
+/// Where a post create step is up to
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StepStatus {
+    Pending,
+    Running,
+    Succeeded,
+    /// The step failed, with the reason why
+    Failed(String),
+    /// The step wasn't run because a required step before it failed or the steps were cancelled
+    Skipped,
+}
+
+/// A post create step and everything that has happened to it
+#[derive(Clone, Debug)]
+pub struct StepState {
+    pub step: PostCreateStep,
+    pub status: StepStatus,
+    /// Every line the step has printed, stdout and stderr interleaved
+    pub output: Vec<String>,
+}
+
+/// A message sent from the thread running the steps
+#[derive(Clone, Debug)]
+pub enum StepEvent {
+    /// A step has started
+    Started(usize),
+    /// A step printed a line
+    Output(usize, String),
+    /// A step has finished, with the reason if it failed
+    Finished(usize, Result<(), String>),
+    /// A step was skipped
+    Skipped(usize),
+}
+
+/// Run the steps one after another on a background thread
+/// ### Parameters
+///  - steps: The steps to run
+///  - dir: The folder to run them in
+///  - cancel: Set to kill the running step and skip the rest
+///
+/// ### Returns
+///  - A receiver for the events of the steps, it disconnects once every step is done
+pub fn spawn(steps: Vec<PostCreateStep>, dir: &Path, cancel: Arc<AtomicBool>) -> Receiver<StepEvent> {
+    let (sender, receiver) = mpsc::channel();
+    let dir = dir.to_path_buf();
+
+    thread::spawn(move || {
+        let mut stopped = false;
+        for (index, step) in steps.iter().enumerate() {
+            if stopped || cancel.load(Ordering::Relaxed) {
+                let _ = sender.send(StepEvent::Skipped(index));
+                continue;
+            }
+
+            let _ = sender.send(StepEvent::Started(index));
+            let result = run_step(index, step, &dir, &sender, &cancel);
+            if result.is_err() && step.required {
+                stopped = true;
+            }
+            let _ = sender.send(StepEvent::Finished(index, result));
+        }
+    });
+
+    receiver
+}
+
+/// Run a single step, streaming its output as it is printed
+fn run_step(index: usize, step: &PostCreateStep, dir: &Path, sender: &Sender<StepEvent>, cancel: &AtomicBool) -> Result<(), String> {
+    let Some((program, args)) = step.command.split_first() else {
+        return Err("empty command".to_string());
+    };
+
+    let mut child = Command::new(program)
+        .args(args)
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("couldn't start `{}`: {}", program, e))?;
+
+    let readers = [
+        child.stdout.take().map(|out| forward_lines(index, out, sender.clone())),
+        child.stderr.take().map(|err| forward_lines(index, err, sender.clone())),
+    ];
+    // Wait for the step without blocking so it can be killed when the steps are cancelled
+    let status = loop {
+        if let Some(status) = child.try_wait().map_err(|e| e.to_string())? {
+            break Some(status);
+        }
+        if cancel.load(Ordering::Relaxed) {
+            let _ = child.kill();
+            let _ = child.wait();
+            break None;
+        }
+        thread::sleep(Duration::from_millis(20));
+    };
+    for reader in readers.into_iter().flatten() {
+        let _ = reader.join();
+    }
+
+    let Some(status) = status else {
+        return Err("cancelled".to_string());
+    };
+    if status.success() {
+        Ok(())
+    } else {
+        match status.code() {
+            Some(code) => Err(format!("exited with code {}", code)),
+            None => Err("killed by a signal".to_string()),
+        }
+    }
+}
+
+/// Send each line read from a pipe as an output event
+fn forward_lines(index: usize, pipe: impl Read + Send + 'static, sender: Sender<StepEvent>) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        for line in BufReader::new(pipe).lines().map_while(Result::ok) {
+            let _ = sender.send(StepEvent::Output(index, line));
+        }
+    })
+}
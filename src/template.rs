@@ -0,0 +1,479 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::hooks;
+use crate::overlay::OverlayManifest;
+use crate::paths;
+use crate::plan::{self, Plan};
+use crate::steps::PostCreateStep;
+
+/// The name of the manifest file at the root of every template
+pub const MANIFEST_FILE: &str = "template.toml";
+/// The folder inside a template holding the files to render
+pub const FILES_DIR: &str = "files";
+/// The variables made from `project_name` in other cases, like `my_app`, `my-app` and `MyApp`
+pub const NAME_VARIANTS: [&str; 3] = ["project_name_snake", "project_name_kebab", "project_name_pascal"];
+
+/// The errors that can happen while loading or rendering a template
+#[derive(Debug)]
+pub enum TemplateError {
+    /// A file in the template couldn't be read
+    Io(PathBuf, io::Error),
+    /// The manifest couldn't be parsed
+    Manifest(PathBuf, String),
+    /// A file couldn't be rendered
+    Render { file: PathBuf, line: usize, message: String },
+    /// A hook script failed
+    Script { hook: String, message: String },
+    /// The answer to a prompt isn't valid, or its condition couldn't be evaluated
+    Prompt { name: String, message: String },
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TemplateError::Io(path, error) => write!(f, "{}: {}", path.display(), error),
+            TemplateError::Manifest(path, message) => write!(f, "{}: {}", path.display(), message),
+            TemplateError::Render { file, line, message } => write!(f, "{}:{}: {}", file.display(), line, message),
+            TemplateError::Script { hook, message } => write!(f, "{} hook: {}", hook, message),
+            TemplateError::Prompt { name, message } => write!(f, "`{}` {}", name, message),
+        }
+    }
+}
+
+/// The hooks section of a template manifest
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct HooksManifest {
+    /// A Rhai script run before the template files are rendered
+    pub pre_generate: Option<PathBuf>,
+    /// A Rhai script run after the template files are rendered
+    pub post_generate: Option<PathBuf>,
+    /// Whether the hooks may queue external commands with `run`
+    pub allow_commands: bool,
+}
+
+/// The kinds of answer a prompt can ask for
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PromptType {
+    #[default]
+    String,
+    Bool,
+    /// One of the prompt's `choices`
+    Choice,
+    Integer,
+}
+
+/// A variable the template asks the user for on the form screen
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Prompt {
+    /// The name of the variable the answer is stored in
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: PromptType,
+    /// The label shown on the form, the name if not given
+    pub label: Option<String>,
+    /// A line explaining the prompt, shown while it is selected
+    pub help: String,
+    /// The starting answer, which can use `{{ }}` variables like `project_name`
+    pub default: Option<toml::Value>,
+    /// The answers a `choice` prompt can have
+    pub choices: Vec<String>,
+    /// A regex a `string` answer must match
+    pub pattern: Option<String>,
+    /// A Rhai expression over the other answers, the prompt is only asked when it is true
+    pub when: Option<String>,
+}
+
+impl Prompt {
+    /// Get the label shown on the form
+    pub fn label(&self) -> &str {
+        self.label.as_deref().unwrap_or(&self.name)
+    }
+
+    /// Get the starting answer, rendering any variables in it
+    pub fn default_value(&self, variables: &BTreeMap<String, String>) -> String {
+        match &self.default {
+            Some(toml::Value::String(text)) => render(text, variables).unwrap_or_else(|_| text.clone()),
+            Some(value) => value.to_string(),
+            None => match self.kind {
+                PromptType::String => String::new(),
+                PromptType::Bool => "false".to_string(),
+                PromptType::Choice => self.choices.first().cloned().unwrap_or_default(),
+                PromptType::Integer => "0".to_string(),
+            },
+        }
+    }
+
+    /// Check an answer, giving the message to show next to the prompt if it isn't valid
+    pub fn validate(&self, value: &str) -> Result<(), String> {
+        match self.kind {
+            PromptType::Bool if value != "true" && value != "false" => Err("must be true or false".to_string()),
+            PromptType::Integer if value.parse::<i64>().is_err() => Err("must be a whole number".to_string()),
+            PromptType::Choice if !self.choices.iter().any(|choice| choice == value) => {
+                Err(format!("must be one of {}", self.choices.join(", ")))
+            }
+            PromptType::String => match &self.pattern {
+                Some(pattern) => {
+                    let re = Regex::new(&format!("^(?:{})$", pattern)).map_err(|_| format!("has an invalid pattern `{}`", pattern))?;
+                    if re.is_match(value) { Ok(()) } else { Err(format!("must match `{}`", pattern)) }
+                }
+                None => Ok(()),
+            },
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether the prompt applies given the other answers
+    pub fn is_asked(&self, variables: &BTreeMap<String, String>, prompts: &[Prompt]) -> Result<bool, String> {
+        match &self.when {
+            Some(expression) => hooks::condition(expression, variables, prompts),
+            None => Ok(true),
+        }
+    }
+}
+
+/// A rule deciding whether some of a template's files are written
+///
+/// Paths are globs relative to the `files` folder, before they are rendered.
+/// `*` matches within a folder, `**` across folders, and a folder matches
+/// everything inside it.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FileRule {
+    /// Files and folders only written when `when` is true
+    pub include: Vec<String>,
+    /// Files and folders left out when `when` is true
+    pub exclude: Vec<String>,
+    /// A Rhai expression over the variables, always true if not given
+    pub when: Option<String>,
+}
+
+/// The contents of a `template.toml`
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Manifest {
+    /// The name shown in the project type list
+    pub name: String,
+    /// A short description of the template
+    pub description: String,
+    /// The version of the template, recorded in the projects created from it
+    pub version: Option<String>,
+    /// The hook scripts of the template
+    pub hooks: HooksManifest,
+    /// The commands to run in the project once it has been created, only run if the hooks may run commands
+    pub post_create: Vec<PostCreateStep>,
+    /// The variables to ask the user for, in order
+    pub prompts: Vec<Prompt>,
+    /// The rules for which files are written
+    pub rules: Vec<FileRule>,
+    /// Makes the template a feature added on top of other projects rather than a project type
+    pub overlay: Option<OverlayManifest>,
+}
+
+/// A template loaded from disk
+#[derive(Clone, Debug)]
+pub struct Template {
+    /// The folder the template was loaded from
+    pub root: PathBuf,
+    /// The parsed manifest
+    pub manifest: Manifest,
+    /// The repository the template was fetched from, if it didn't come from the templates folder
+    pub source: Option<String>,
+    /// The commit of the repository the template was fetched at
+    pub commit: Option<String>,
+}
+
+impl Template {
+    /// Load the template in a folder
+    pub fn load(root: &Path) -> Result<Template, TemplateError> {
+        let manifest_path = root.join(MANIFEST_FILE);
+        let text = fs::read_to_string(&manifest_path).map_err(|e| TemplateError::Io(manifest_path.clone(), e))?;
+        let mut manifest: Manifest = toml::from_str(&text)
+            .map_err(|e| TemplateError::Manifest(manifest_path.clone(), e.message().to_string()))?;
+
+        if manifest.name.is_empty() {
+            manifest.name = root
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| "Template".to_string());
+        }
+
+        Ok(Template { root: root.to_path_buf(), manifest, source: None, commit: None })
+    }
+
+    /// Get the name of the folder the template is in, which names it in recipes when it is a feature
+    pub fn id(&self) -> String {
+        self.root.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default()
+    }
+
+    /// Build the plan for a project from this template
+    /// ### Parameters
+    ///  - project_root: The folder the project will be created in
+    ///  - variables: The variables available to the template
+    pub fn plan(&self, project_root: &Path, mut variables: BTreeMap<String, String>) -> Result<Plan, TemplateError> {
+        self.resolve_prompts(&mut variables)?;
+        let mut plan = Plan::new(project_root);
+        plan.variables = variables;
+
+        let files_root = self.root.join(FILES_DIR);
+        if files_root.is_dir() {
+            collect_files(&files_root, &files_root, &mut plan)?;
+        }
+
+        let hooks = &self.manifest.hooks;
+        if let Some(script) = &hooks.pre_generate {
+            hooks::run_hook("pre_generate", &self.root.join(script), &mut plan, hooks.allow_commands)?;
+        }
+        self.apply_rules(&mut plan)?;
+
+        for file in &mut plan.files {
+            let path = render(&file.path.to_string_lossy(), &plan.variables)
+                .map_err(|(line, message)| TemplateError::Render { file: file.path.clone(), line, message })?;
+            file.contents = render(&file.contents, &plan.variables)
+                .map_err(|(line, message)| TemplateError::Render { file: file.path.clone(), line, message })?;
+            plan::check_path(Path::new(&path)).map_err(|message| TemplateError::Render { file: file.path.clone(), line: 1, message })?;
+            file.path = PathBuf::from(path);
+        }
+        for directory in &mut plan.directories {
+            let path = render(&directory.to_string_lossy(), &plan.variables)
+                .map_err(|(line, message)| TemplateError::Render { file: directory.clone(), line, message })?;
+            plan::check_path(Path::new(&path)).map_err(|message| TemplateError::Render { file: directory.clone(), line: 1, message })?;
+            *directory = PathBuf::from(path);
+        }
+
+        if let Some(script) = &hooks.post_generate {
+            hooks::run_hook("post_generate", &self.root.join(script), &mut plan, hooks.allow_commands)?;
+        }
+
+        Ok(plan)
+    }
+
+    /// Leave out the files and folders the manifest's rules exclude, noting them in the plan
+    fn apply_rules(&self, plan: &mut Plan) -> Result<(), TemplateError> {
+        for (index, rule) in self.manifest.rules.iter().enumerate() {
+            let rule_error = |message: String| {
+                TemplateError::Manifest(self.root.join(MANIFEST_FILE), format!("rule {} {}", index + 1, message))
+            };
+            let active = match &rule.when {
+                Some(expression) => hooks::condition(expression, &plan.variables, &self.manifest.prompts).map_err(rule_error)?,
+                None => true,
+            };
+            let globs = if active { &rule.exclude } else { &rule.include };
+            let matchers = globs.iter().map(|glob| glob_regex(glob)).collect::<Result<Vec<Regex>, String>>().map_err(rule_error)?;
+            let matches = |path: &Path| {
+                let path = path.to_string_lossy().replace('\\', "/");
+                matchers.iter().any(|matcher| matcher.is_match(&path))
+            };
+
+            let skipped = plan.files.iter().map(|file| &file.path).chain(&plan.directories).filter(|path| matches(path));
+            let skipped: Vec<PathBuf> = skipped.cloned().collect();
+            plan.files.retain(|file| !matches(&file.path));
+            plan.directories.retain(|directory| !matches(directory));
+            plan.skipped.extend(skipped);
+        }
+        Ok(())
+    }
+
+    /// Give every prompt without an answer its default, then check the answers to the prompts that are asked
+    ///
+    /// Prompts that aren't asked still get their default so the files can always use them.
+    pub fn resolve_prompts(&self, variables: &mut BTreeMap<String, String>) -> Result<(), TemplateError> {
+        add_name_variants(variables);
+        let prompts = &self.manifest.prompts;
+        for prompt in prompts {
+            if !variables.contains_key(&prompt.name) {
+                let value = prompt.default_value(variables);
+                variables.insert(prompt.name.clone(), value);
+            }
+        }
+
+        for prompt in prompts {
+            let prompt_error = |message: String| TemplateError::Prompt { name: prompt.name.clone(), message };
+            if prompt.is_asked(variables, prompts).map_err(prompt_error)? {
+                prompt.validate(&variables[&prompt.name]).map_err(prompt_error)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Add `project_name` in snake, kebab and Pascal case, unless they are already given
+pub fn add_name_variants(variables: &mut BTreeMap<String, String>) {
+    let Some(name) = variables.get("project_name") else {
+        return;
+    };
+    let [snake, kebab, pascal] = name_variants(name);
+    for (variable, value) in NAME_VARIANTS.into_iter().zip([snake, kebab, pascal]) {
+        variables.entry(variable.to_string()).or_insert(value);
+    }
+}
+
+/// Get a name in snake, kebab and Pascal case
+///
+/// Words are split at anything that isn't a letter or digit and where a lowercase letter meets an uppercase one.
+pub fn name_variants(name: &str) -> [String; 3] {
+    let mut words: Vec<String> = Vec::new();
+    let mut previous_lower = false;
+    for c in name.chars() {
+        if !c.is_alphanumeric() {
+            previous_lower = false;
+            words.push(String::new());
+            continue;
+        }
+        if c.is_uppercase() && previous_lower {
+            words.push(String::new());
+        }
+        previous_lower = c.is_lowercase() || c.is_ascii_digit();
+        match words.last_mut() {
+            Some(word) => word.extend(c.to_lowercase()),
+            None => words.push(c.to_lowercase().collect()),
+        }
+    }
+    words.retain(|word| !word.is_empty());
+
+    let pascal = words
+        .iter()
+        .map(|word| {
+            let mut chars = word.chars();
+            chars.next().map(|first| first.to_uppercase().chain(chars).collect::<String>()).unwrap_or_default()
+        })
+        .collect();
+    [words.join("_"), words.join("-"), pascal]
+}
+
+/// Find every template in the user's templates folder
+///
+/// Folders that fail to load are skipped so one broken template can't hide the others
+pub fn discover() -> Vec<Template> {
+    let Some(dir) = paths::templates_dir() else {
+        return Vec::new();
+    };
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut templates: Vec<Template> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.join(MANIFEST_FILE).is_file())
+        .filter_map(|path| Template::load(&path).ok())
+        .collect();
+    templates.sort_by(|a, b| a.manifest.name.cmp(&b.manifest.name));
+    templates
+}
+
+/// Turn a glob into a regex matching the paths it names and everything inside them
+pub fn glob_regex(glob: &str) -> Result<Regex, String> {
+    let mut pattern = String::from("^");
+    let mut chars = glob.trim_end_matches('/').chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.next_if_eq(&'/').is_some() {
+                    pattern.push_str("(?:.*/)?");
+                } else {
+                    pattern.push_str(".*");
+                }
+            }
+            '*' => pattern.push_str("[^/]*"),
+            '?' => pattern.push_str("[^/]"),
+            c => pattern.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    pattern.push_str("(?:/.*)?$");
+    Regex::new(&pattern).map_err(|_| format!("has an invalid path `{}`", glob))
+}
+
+/// Add every file under `dir` to the plan, unrendered
+pub fn collect_files(files_root: &Path, dir: &Path, plan: &mut Plan) -> Result<(), TemplateError> {
+    let entries = fs::read_dir(dir).map_err(|e| TemplateError::Io(dir.to_path_buf(), e))?;
+    let mut paths: Vec<PathBuf> = entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()).collect();
+    paths.sort();
+
+    let relative = |path: &Path| path.strip_prefix(files_root).unwrap_or(path).to_path_buf();
+    if paths.is_empty() && dir != files_root {
+        plan.add_directory(relative(dir));
+    }
+
+    for path in paths {
+        if path.is_dir() {
+            collect_files(files_root, &path, plan)?;
+        } else {
+            let contents = fs::read_to_string(&path).map_err(|e| match e.kind() {
+                // Files are rendered as text, so a binary file can't be part of a template
+                io::ErrorKind::InvalidData => {
+                    TemplateError::Io(path.clone(), io::Error::new(e.kind(), "isn't UTF-8 text, binary files aren't supported in templates"))
+                }
+                _ => TemplateError::Io(path.clone(), e),
+            })?;
+            plan.add_file(relative(&path), contents);
+        }
+    }
+    Ok(())
+}
+
+/// Replace every `{{ variable }}` in the text with its value
+///
+/// A backslash before the braces (`\{{`) writes them out literally.
+/// ### Returns
+///  - The rendered text, or the line and message of the first error
+pub fn render(text: &str, variables: &BTreeMap<String, String>) -> Result<String, (usize, String)> {
+    substitute(text, |_, name| variables.get(name).cloned().ok_or_else(|| format!("undefined variable `{}`", name)))
+}
+
+/// Find every `{{ variable }}` in the text without rendering it
+///
+/// The line and name of each variable up to the first syntax error are added to `found`.
+/// ### Returns
+///  - The line and message of the first syntax error
+pub fn references(text: &str, found: &mut Vec<(usize, String)>) -> Result<(), (usize, String)> {
+    substitute(text, |line, name| {
+        found.push((line, name.to_string()));
+        Ok(String::new())
+    })
+    .map(|_| ())
+}
+
+/// Replace every `{{ variable }}` in the text with what `value` gives for its line and name
+fn substitute(text: &str, mut value: impl FnMut(usize, &str) -> Result<String, String>) -> Result<String, (usize, String)> {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+    let mut line = 1;
+
+    while let Some(start) = rest.find("{{") {
+        let before = &rest[..start];
+        if let Some(literal) = before.strip_suffix('\\') {
+            output.push_str(literal);
+            output.push_str("{{");
+            line += before.matches('\n').count();
+            rest = &rest[start + 2..];
+            continue;
+        }
+        output.push_str(before);
+        line += before.matches('\n').count();
+
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            return Err((line, "unclosed `{{`".to_string()));
+        };
+        let name = after[..end].trim();
+        if name.contains('\n') || name.is_empty() {
+            return Err((line, "expected a variable name inside `{{ }}`".to_string()));
+        }
+        output.push_str(&value(line, name).map_err(|message| (line, message))?);
+        rest = &after[end + 2..];
+    }
+    output.push_str(rest);
+
+    Ok(output)
+}
@@ -0,0 +1,107 @@
+use std::io;
+use std::panic;
+use std::process;
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use ratatui::crossterm::{
+    cursor::Show,
+    event::{DisableMouseCapture, EnableMouseCapture, KeyCode, KeyEvent, KeyModifiers},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+
+/// How long a TUI gets to close itself after a signal before the terminal is restored for it
+const SIGNAL_GRACE: Duration = Duration::from_secs(1);
+
+/// Whether the terminal is in raw mode on the alternate screen, so it needs restoring
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+/// The signal that asked the app to stop, 0 if none has
+static SIGNAL: AtomicI32 = AtomicI32::new(0);
+
+/// Put the terminal in raw mode on the alternate screen for a TUI
+/// ### Parameters
+///  - mouse_capture: Whether to capture the mouse, which stops the terminal selecting text
+pub fn enter(mouse_capture: bool) -> io::Result<()> {
+    enable_raw_mode()?;
+    ACTIVE.store(true, Ordering::SeqCst);
+    if mouse_capture {
+        execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture)
+    } else {
+        execute!(io::stdout(), EnterAlternateScreen)
+    }
+}
+
+/// Give the terminal back to the shell, doing nothing if it already has been
+///
+/// Every step is tried even if one fails, as this also runs while panicking.
+pub fn restore() {
+    if ACTIVE.swap(false, Ordering::SeqCst) {
+        let _ = disable_raw_mode();
+        let _ = execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture, Show);
+    }
+}
+
+/// Restore the terminal before a panic's message is printed, so it can be read and the shell still works
+pub fn install_panic_hook() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        restore();
+        default_hook(info);
+    }));
+}
+
+/// Restore the terminal when the app is asked to stop with SIGINT, SIGTERM or SIGHUP
+///
+/// A TUI that is running is given a moment to close itself, which it does once it sees
+/// `interrupted`. Otherwise the terminal is restored here and the app exits straight away
+/// with 128 plus the signal's number, like the default handlers do.
+#[cfg(unix)]
+pub fn install_signal_handlers() -> io::Result<()> {
+    use signal_hook::consts::{SIGHUP, SIGINT, SIGTERM};
+    use signal_hook::iterator::Signals;
+
+    let mut signals = Signals::new([SIGINT, SIGTERM, SIGHUP])?;
+    thread::spawn(move || {
+        for signal in signals.forever() {
+            SIGNAL.store(signal, Ordering::SeqCst);
+            let in_tui = ACTIVE.load(Ordering::SeqCst);
+            let started = Instant::now();
+            while ACTIVE.load(Ordering::SeqCst) && started.elapsed() < SIGNAL_GRACE {
+                thread::sleep(Duration::from_millis(20));
+            }
+            // The TUI closed itself and exits once it has tidied up
+            if in_tui && !ACTIVE.load(Ordering::SeqCst) {
+                continue;
+            }
+            restore();
+            process::exit(128 + signal);
+        }
+    });
+    Ok(())
+}
+
+/// Ctrl+C and SIGTERM can't be caught on Windows in the same way, Ctrl+C still arrives as a key in the TUI
+#[cfg(not(unix))]
+pub fn install_signal_handlers() -> io::Result<()> {
+    Ok(())
+}
+
+/// Whether a signal has asked the app to stop
+pub fn interrupted() -> bool {
+    SIGNAL.load(Ordering::SeqCst) != 0
+}
+
+/// Get the code to exit with after a signal asked the app to stop
+pub fn signal_exit_code() -> Option<i32> {
+    match SIGNAL.load(Ordering::SeqCst) {
+        0 => None,
+        signal => Some(128 + signal),
+    }
+}
+
+/// Whether a key is Ctrl+C, which raw mode delivers as a key instead of a signal
+pub fn is_ctrl_c(key: &KeyEvent) -> bool {
+    key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL)
+}
@@ -0,0 +1,172 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::app::ProjectTypes;
+
+/// The external tools the built in project types can use
+pub const TOOLS: [&str; 5] = ["python3", "uv", "cargo", "cmake", "git"];
+
+/// A tool found on PATH
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ToolInfo {
+    /// Where the tool was found
+    pub path: PathBuf,
+    /// The first line the tool printed for `--version`, if it printed anything
+    pub version: Option<String>,
+}
+
+/// Whether a project type can be created with the tools that were found
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Availability {
+    /// Everything needed was found
+    Ready,
+    /// Something was missing but the project can still be created, with the reason why
+    Degraded(String),
+    /// The project can't be created, with the reason why
+    Missing(String),
+}
+
+/// The tools found when the app started
+#[derive(Clone, Debug, Default)]
+pub struct Toolchain {
+    tools: BTreeMap<&'static str, ToolInfo>,
+}
+
+impl Toolchain {
+    /// Look for every tool on PATH and ask it for its version
+    pub fn probe() -> Toolchain {
+        let mut tools = BTreeMap::new();
+        for tool in TOOLS {
+            let program = program_name(tool);
+            if let Some(path) = find_on_path(program) {
+                let version = tool_version(&path);
+                tools.insert(tool, ToolInfo { path, version });
+            }
+        }
+        Toolchain { tools }
+    }
+
+    /// Get a tool if it was found
+    pub fn get(&self, tool: &str) -> Option<&ToolInfo> {
+        self.tools.get(tool)
+    }
+
+    /// Whether a tool was found
+    pub fn has(&self, tool: &str) -> bool {
+        self.tools.contains_key(tool)
+    }
+
+    /// Get the version of the tool, or why it is missing, for showing to the user
+    pub fn describe(&self, tool: &str) -> String {
+        match self.get(tool) {
+            Some(ToolInfo { version: Some(version), .. }) => version.clone(),
+            Some(ToolInfo { path, .. }) => format!("{} ({})", tool, path.display()),
+            None => not_found(tool),
+        }
+    }
+
+    /// Get the `major.minor` version of Python, if it was found
+    pub fn python_version(&self) -> Option<String> {
+        let version = self.get("python3")?.version.as_ref()?;
+        let number = version.split_whitespace().nth(1)?;
+        let mut parts = number.split('.');
+        Some(format!("{}.{}", parts.next()?, parts.next()?))
+    }
+
+    /// Work out whether a project type can be created
+    pub fn availability(&self, project_type: ProjectTypes) -> Availability {
+        match project_type {
+            ProjectTypes::Python if !self.has("python3") => Availability::Missing(not_found("python3")),
+            ProjectTypes::UvPython if !self.has("uv") => Availability::Degraded(not_found("uv")),
+            ProjectTypes::Rust if !self.has("cargo") => Availability::Missing(not_found("cargo")),
+            ProjectTypes::CmakeCpp if !self.has("cmake") => Availability::Degraded(not_found("cmake")),
+            _ => Availability::Ready,
+        }
+    }
+
+    /// Explain what happens when a degraded project type is created anyway
+    pub fn fallback(project_type: ProjectTypes) -> Option<&'static str> {
+        match project_type {
+            ProjectTypes::UvPython => Some("The project will be written without uv, run `uv sync` once it is installed"),
+            ProjectTypes::CmakeCpp => Some("The project will be written, but you will need CMake to build it"),
+            _ => None,
+        }
+    }
+
+    /// Get the tool a project type mostly relies on
+    pub fn main_tool(project_type: ProjectTypes) -> Option<&'static str> {
+        match project_type {
+            ProjectTypes::Python => Some("python3"),
+            ProjectTypes::UvPython => Some("uv"),
+            ProjectTypes::Rust => Some("cargo"),
+            ProjectTypes::CmakeCpp => Some("cmake"),
+            ProjectTypes::Template(_) => None,
+        }
+    }
+}
+
+/// The message for a tool that isn't on PATH
+fn not_found(tool: &str) -> String {
+    format!("{} not found on PATH", program_name(tool))
+}
+
+/// Get the program to look for on PATH for a tool
+pub fn program_name(tool: &str) -> &str {
+    match tool {
+        "python3" if cfg!(target_os = "windows") => "python",
+        tool => tool,
+    }
+}
+
+/// Find a program in the folders on PATH, trying each of `PATHEXT` on Windows
+pub fn find_on_path(program: &str) -> Option<PathBuf> {
+    let path = env::var_os("PATH")?;
+    let extensions: Vec<String> = if cfg!(target_os = "windows") {
+        env::var("PATHEXT")
+            .unwrap_or_else(|_| ".EXE;.CMD;.BAT".to_string())
+            .split(';')
+            .map(str::to_string)
+            .collect()
+    } else {
+        vec![String::new()]
+    };
+
+    env::split_paths(&path).find_map(|dir| {
+        extensions
+            .iter()
+            .map(|extension| dir.join(format!("{}{}", program, extension)))
+            .find(|candidate| is_executable(candidate))
+    })
+}
+
+/// Whether a path is a file that can be run
+fn is_executable(path: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        path.metadata().map(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0).unwrap_or(false)
+    }
+    #[cfg(not(unix))]
+    {
+        path.is_file()
+    }
+}
+
+/// Ask a tool for its version, returning the first line it prints
+fn tool_version(path: &Path) -> Option<String> {
+    let output = Command::new(path)
+        .arg("--version")
+        .stdin(Stdio::null())
+        .output()
+        .ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    stdout
+        .lines()
+        .chain(stderr.lines())
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(str::to_string)
+}
@@ -3,10 +3,20 @@ use ratatui::{
     layout::{Constraint, Direction, Layout, Rect, Alignment},
     style::{Color, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Paragraph},
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table, Wrap},
 };
 
-use crate::app::{App, CurrentScreen, ProjectTypes};
+use crate::app::{checkbox, App, CurrentScreen, ProjectFeature, ProjectTypes};
+use crate::batch::{Job, JobStatus};
+use crate::error::AppError;
+use crate::form::Form;
+use crate::highlight::highlight;
+use crate::plan::display_command;
+use crate::steps::StepStatus;
+use crate::template::PromptType;
+use crate::toolchain::{Availability, Toolchain};
+use crate::merge::Chunk;
+use crate::update::{self, FileChange, FileUpdate};
 
 pub fn ui(frame: &mut Frame, app: &App) {
     let whole = frame.area();
@@ -36,6 +46,11 @@ pub fn ui(frame: &mut Frame, app: &App) {
             CurrentScreen::SelectProjectType => Span::styled("Select Project", Style::default().fg(Color::Blue)),
             CurrentScreen::SelectProjectName => Span::styled("Select Name", Style::default().fg(Color::Blue)),
             CurrentScreen::SelectProjectFolder => Span::styled("Select Folder", Style::default().fg(Color::Blue)),
+            CurrentScreen::Metadata => Span::styled("Project Details", Style::default().fg(Color::Blue)),
+            CurrentScreen::TemplatePrompts => Span::styled("Template Questions", Style::default().fg(Color::Blue)),
+            CurrentScreen::SelectProjectOptions => Span::styled("Select Options", Style::default().fg(Color::Blue)),
+            CurrentScreen::SelectFeatures => Span::styled("Select Features", Style::default().fg(Color::Blue)),
+            CurrentScreen::CreateProject if app.dry_run => Span::styled("Confirm Project (dry run)", Style::default().fg(Color::Red)),
             CurrentScreen::CreateProject => Span::styled("Confirm Project", Style::default().fg(Color::Red)),
             CurrentScreen::ProjectCreated => Span::styled("Project Created", Style::default().fg(Color::Red)),
         }
@@ -49,6 +64,11 @@ pub fn ui(frame: &mut Frame, app: &App) {
         CurrentScreen::Main => Span::styled("(q) quit / (e) new project", Style::default().fg(Color::LightBlue)),
         CurrentScreen::SelectProjectName => Span::styled("(enter) continue", Style::default().fg(Color::LightBlue)),
         CurrentScreen::SelectProjectFolder => Span::styled("(enter) continue", Style::default().fg(Color::LightBlue)),
+        CurrentScreen::CreateProject => Span::styled("(↑/↓) file / (pgup/pgdn) scroll / (x) save script / (r) save recipe / (enter) confirm", Style::default().fg(Color::LightBlue)),
+        CurrentScreen::Metadata => Span::styled("(tab) next / (enter) continue", Style::default().fg(Color::LightBlue)),
+        CurrentScreen::TemplatePrompts => Span::styled("(tab) next / (←/→) change / (enter) continue", Style::default().fg(Color::LightBlue)),
+        CurrentScreen::SelectProjectOptions => Span::styled("(q) quit / (←/→/space) change / (enter) continue", Style::default().fg(Color::LightBlue)),
+        CurrentScreen::SelectFeatures => Span::styled("(space) toggle / (a) all / (enter) continue", Style::default().fg(Color::LightBlue)),
         _ => Span::styled("(q) quit / (enter) continue", Style::default().fg(Color::LightBlue)),
     };
 
@@ -69,36 +89,72 @@ pub fn ui(frame: &mut Frame, app: &App) {
         CurrentScreen::Main => {
             let area = safe_centred_rect(50, 10, chunks[0]);
             let text = Span::styled("Press 'e' to create a new project", Style::default().fg(Color::White));
-            let para = Paragraph::new(Line::from(text))
+            let mut lines = vec![Line::from(text)];
+            if let Some(error) = &app.error {
+                lines.extend(error_lines(error));
+            }
+            let para = Paragraph::new(lines)
+                .wrap(Wrap { trim: true })
                 .alignment(Alignment::Center)
                 .block(Block::default().borders(Borders::NONE));
             frame.render_widget(para, area);
         }
         CurrentScreen::SelectProjectType => {
-            let area = safe_centred_rect(22, 60, chunks[0]);
+            let area = safe_centred_rect(50, 80, chunks[0]);
             // Ensure minimum width for labels; fallback to full chunk if too small
             let area = if area.width < 14 { chunks[0] } else { area };
+            let types = app.project_types();
+            let mut constraints = vec![Constraint::Length(3); types.len()];
+            constraints.push(Constraint::Min(0));
             let items_layout = Layout::default()
                 .direction(Direction::Vertical)
-                .constraints([
-                    Constraint::Length(3),
-                    Constraint::Length(3),
-                    Constraint::Length(3),
-                    Constraint::Length(3),
-                ])
+                .constraints(constraints)
                 .split(area);
 
-                let mk = |label: &'static str, active: bool| {
-                    Paragraph::new(Span::styled(label, Style::default().fg(Color::White)))
-                        .alignment(Alignment::Center)
-                        .block(Block::default().borders(Borders::ALL))
-                        .style(if active { Style::default().bg(Color::Blue) } else { Style::default() })
+            // Explain the highlighted type if it can't be created normally
+            let note = match app.toolchain.availability(app.project_type) {
+                Availability::Ready => None,
+                Availability::Degraded(_) => Toolchain::fallback(app.project_type).map(|note| (note.to_string(), Color::Yellow)),
+                Availability::Missing(reason) => Some((format!("Install it to create this type of project ({})", reason), Color::DarkGray)),
+            };
+            if let Some((note, colour)) = note {
+                let para = Paragraph::new(Span::styled(note, Style::default().fg(colour)))
+                    .alignment(Alignment::Center)
+                    .wrap(Wrap { trim: true });
+                frame.render_widget(para, items_layout[types.len()]);
+            }
+
+            for (project_type, item_area) in types.into_iter().zip(items_layout.iter()) {
+                let label = match project_type {
+                    ProjectTypes::Python => "Python".to_string(),
+                    ProjectTypes::UvPython => "Python + UV".to_string(),
+                    ProjectTypes::Rust => "Rust".to_string(),
+                    ProjectTypes::CmakeCpp => "C++ (CMake)".to_string(),
+                    ProjectTypes::Template(_) => app.project_type_name(project_type),
+                };
+                let (detail, colour) = match app.toolchain.availability(project_type) {
+                    Availability::Ready => (
+                        Toolchain::main_tool(project_type).map(|tool| app.toolchain.describe(tool)).or_else(|| match project_type {
+                            ProjectTypes::Template(index) => app.templates[index].source.as_ref().map(|source| format!("recent template from {}", source)),
+                            _ => None,
+                        }),
+                        Color::White,
+                    ),
+                    Availability::Degraded(reason) => (Some(reason), Color::Yellow),
+                    Availability::Missing(reason) => (Some(reason), Color::DarkGray),
                 };
 
-            frame.render_widget(mk("Python", app.project_type == ProjectTypes::Python), items_layout[0]);
-            frame.render_widget(mk("Python + UV", app.project_type == ProjectTypes::UvPython), items_layout[1]);
-            frame.render_widget(mk("Rust", app.project_type == ProjectTypes::Rust), items_layout[2]);
-            frame.render_widget(mk("C++ (CMake)", app.project_type == ProjectTypes::CmakeCpp), items_layout[3]);
+                let mut spans = vec![Span::styled(label, Style::default().fg(colour))];
+                if let Some(detail) = detail {
+                    spans.push(Span::styled(format!(" - {}", detail), Style::default().fg(if colour == Color::White { Color::Gray } else { colour })));
+                }
+                let active = app.project_type == project_type;
+                let item = Paragraph::new(Line::from(spans))
+                    .alignment(Alignment::Center)
+                    .block(Block::default().borders(Borders::ALL))
+                    .style(if active { Style::default().bg(Color::Blue) } else { Style::default() });
+                frame.render_widget(item, *item_area);
+            }
         }
         CurrentScreen::SelectProjectName => {
             let area = safe_centred_rect(60, 25, chunks[0]);
@@ -120,35 +176,460 @@ pub fn ui(frame: &mut Frame, app: &App) {
                 true,
             );
         }
-        CurrentScreen::CreateProject => {
-            let area = safe_centred_rect(60, 40, chunks[0]);
-            if let (Some(project_name), Some(project_folder)) = (&app.project_name, &app.project_folder) {
-                let lines = vec![
-                    Line::from(Span::styled(format!("Project Type: {}", app.project_type), Style::default().fg(Color::White))),
-                    Line::from(Span::styled(format!("Project Name: {}", project_name), Style::default().fg(Color::White))),
-                    Line::from(Span::styled(format!("Project Folder: {}", project_folder), Style::default().fg(Color::White))),
-                    Line::from(Span::styled("Press (enter) to confirm", Style::default().fg(Color::White))),
-                ];
-                let para = Paragraph::new(lines)
-                    .alignment(Alignment::Center)
-                    .block(Block::default().borders(Borders::ALL).title("Confirm"));
-                frame.render_widget(para, area);
+        CurrentScreen::Metadata | CurrentScreen::TemplatePrompts => {
+            if let Some(form) = &app.form {
+                render_form(frame, form, safe_centred_rect(60, 90, chunks[0]));
             }
         }
-        CurrentScreen::ProjectCreated => {
-            let area = safe_centred_rect(60, 25, chunks[0]);
-            let lines = vec![
-                Line::from(Span::styled("Project created successfully", Style::default().fg(Color::Green))),
-                Line::from(Span::styled("Press any key to continue", Style::default().fg(Color::White))),
-            ];
+        CurrentScreen::SelectProjectOptions => {
+            let area = safe_centred_rect(60, 40, chunks[0]);
+            let options = app.available_options();
+            let lines: Vec<Line> = if options.is_empty() {
+                vec![Line::from(Span::styled("No options for this project type", Style::default().fg(Color::DarkGray)))]
+            } else {
+                options
+                    .iter()
+                    .enumerate()
+                    .map(|(index, option)| {
+                        let (label, value) = app.option_value(*option);
+                        let style = if index == app.option_index {
+                            Style::default().fg(Color::White).bg(Color::Blue)
+                        } else {
+                            Style::default().fg(Color::White)
+                        };
+                        Line::from(Span::styled(format!("{}: < {} >", label, value), style))
+                    })
+                    .collect()
+            };
             let para = Paragraph::new(lines)
                 .alignment(Alignment::Center)
-                .block(Block::default().borders(Borders::ALL).title("Done"));
+                .block(Block::default().borders(Borders::ALL).title("Options"));
             frame.render_widget(para, area);
         }
+        CurrentScreen::SelectFeatures => {
+            let area = safe_centred_rect(70, 70, chunks[0]);
+            render_features(frame, app, area);
+        }
+        CurrentScreen::CreateProject => {
+            let area = safe_centred_rect(90, 90, chunks[0]);
+            render_preview(frame, app, area);
+        }
+        CurrentScreen::ProjectCreated => {
+            if app.steps.is_empty() {
+                let area = safe_centred_rect(60, 25, chunks[0]);
+                let para = Paragraph::new(created_lines(app))
+                    .alignment(Alignment::Center)
+                    .wrap(Wrap { trim: true })
+                    .block(Block::default().borders(Borders::ALL).title("Done"));
+                frame.render_widget(para, area);
+            } else {
+                let area = safe_centred_rect(80, 80, chunks[0]);
+                let step_count = app.steps.len() as u16;
+                let rows = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([
+                        Constraint::Length(5),
+                        Constraint::Length(step_count + 2),
+                        Constraint::Min(3),
+                    ])
+                    .split(area);
+
+                let para = Paragraph::new(created_lines(app))
+                    .alignment(Alignment::Center)
+                    .wrap(Wrap { trim: true })
+                    .block(Block::default().borders(Borders::ALL).title("Done"));
+                frame.render_widget(para, rows[0]);
+                render_steps(frame, app, rows[1], rows[2]);
+            }
+        }
     }
 }
 
+/// Render the features checklist, with the description of the highlighted add-on below it
+fn render_features(frame: &mut Frame, app: &App, area: Rect) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(4)])
+        .split(area);
+
+    let features = app.feature_list();
+    let lines: Vec<Line> = features
+        .iter()
+        .enumerate()
+        .map(|(index, feature)| {
+            let (name, _) = app.feature_info(*feature);
+            let unavailable = app.feature_unavailable(*feature);
+            let colour = if unavailable.is_some() { Color::DarkGray } else { Color::White };
+            let style = if index == app.feature_index {
+                Style::default().fg(colour).bg(Color::Blue)
+            } else {
+                Style::default().fg(colour)
+            };
+            let mut spans = vec![Span::styled(format!("{} {}", checkbox(app.feature_checked(*feature)), name), style)];
+            if let Some(reason) = unavailable {
+                spans.push(Span::styled(format!("  ({})", reason), Style::default().fg(Color::DarkGray)));
+            }
+            Line::from(spans)
+        })
+        .collect();
+    // Keep the highlighted add-on in view when there are more than fit
+    let height = rows[0].height.saturating_sub(2) as usize;
+    let scroll = (app.feature_index + 1).saturating_sub(height) as u16;
+    let list = Paragraph::new(lines)
+        .scroll((scroll, 0))
+        .block(Block::default().borders(Borders::ALL).title("Features"));
+    frame.render_widget(list, rows[0]);
+
+    let description = features.get(app.feature_index).map(|feature| app.feature_info(*feature).1).unwrap_or_default();
+    let para = Paragraph::new(description)
+        .wrap(Wrap { trim: true })
+        .style(Style::default().fg(Color::Gray))
+        .block(Block::default().borders(Borders::ALL));
+    frame.render_widget(para, rows[1]);
+}
+
+/// Render the confirmation screen: a summary, the file tree and commands, and the selected file
+fn render_preview(frame: &mut Frame, app: &App, area: Rect) {
+    let (Some(project_name), Some(project_folder)) = (&app.project_name, &app.project_folder) else {
+        return;
+    };
+
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+        .split(area);
+
+    let white = Style::default().fg(Color::White);
+    let heading = Style::default().fg(Color::LightBlue);
+    let mut lines = vec![
+        Line::from(Span::styled(format!("Project Type: {}", app.project_type_name(app.project_type)), white)),
+        Line::from(Span::styled(format!("Project Name: {}", project_name), white)),
+        Line::from(Span::styled(format!("Project Folder: {}", project_folder), white)),
+        Line::from(Span::styled(format!("CI: {}", app.options.ci), white)),
+        Line::from(Span::styled(format!("Licence: {}", app.options.licence), white)),
+    ];
+    let features: Vec<String> = app
+        .feature_list()
+        .into_iter()
+        .filter(|feature| app.feature_checked(*feature))
+        // CI and the licence have lines of their own
+        .filter(|feature| !matches!(feature, ProjectFeature::Ci | ProjectFeature::Licence))
+        .map(|feature| app.feature_info(feature).0)
+        .collect();
+    let features = if features.is_empty() { "none".to_string() } else { features.join(", ") };
+    lines.push(Line::from(Span::styled(format!("Features: {}", features), white)));
+
+    if let Some(message) = &app.message {
+        lines.push(Line::from(Span::styled(message.clone(), Style::default().fg(Color::Yellow))));
+    }
+
+    let mut selected_line = 0;
+    match (&app.plan, &app.error) {
+        (_, Some(error)) => {
+            lines.push(Line::from(""));
+            lines.extend(error_lines(error));
+        }
+        (Some(plan), None) => {
+            let sections = [("Commands", &plan.commands), ("Post commands", &plan.post_commands)];
+            for (title, commands) in sections {
+                if !commands.is_empty() {
+                    lines.push(Line::from(""));
+                    lines.push(Line::from(Span::styled(format!("{}:", title), heading)));
+                    for command in commands {
+                        lines.push(Line::from(Span::styled(format!("$ {}", display_command(&command.argv())), Style::default().fg(Color::Gray))));
+                    }
+                }
+            }
+            if !plan.post_create.is_empty() {
+                lines.push(Line::from(""));
+                lines.push(Line::from(Span::styled("Post create steps:", heading)));
+                for step in &plan.post_create {
+                    lines.push(Line::from(Span::styled(format!("$ {}", display_command(&step.command)), Style::default().fg(Color::Gray))));
+                }
+            }
+
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled("Files:", heading)));
+            selected_line = lines.len() + app.preview_selected;
+            for (index, entry) in plan.tree().into_iter().enumerate() {
+                let colour = if entry.file.is_some() { Color::White } else { Color::LightBlue };
+                let style = if index == app.preview_selected {
+                    Style::default().fg(colour).bg(Color::Blue)
+                } else {
+                    Style::default().fg(colour)
+                };
+                lines.push(Line::from(Span::styled(format!("{}{}", "  ".repeat(entry.depth), entry.name), style)));
+            }
+
+            if !plan.skipped.is_empty() {
+                lines.push(Line::from(""));
+                lines.push(Line::from(Span::styled("Skipped by the template's rules:", heading)));
+                for path in &plan.skipped {
+                    lines.push(Line::from(Span::styled(path.display().to_string(), Style::default().fg(Color::DarkGray))));
+                }
+            }
+        }
+        (None, None) => {}
+    }
+
+    // Scroll the summary just far enough to keep the selected file in view
+    let visible = columns[0].height.saturating_sub(2) as usize;
+    let offset = (selected_line + 1).saturating_sub(visible) as u16;
+    let summary = Paragraph::new(lines)
+        .scroll((offset, 0))
+        .block(Block::default().borders(Borders::ALL).title("Confirm"));
+    frame.render_widget(summary, columns[0]);
+
+    let (title, contents) = match app.preview_file() {
+        Some(file) => (file.path.display().to_string(), highlight(&file.path, &file.contents)),
+        None => ("Preview".to_string(), vec![Line::from("Select a file to see its contents")]),
+    };
+    let contents = Paragraph::new(contents)
+        .style(Style::default().fg(Color::Gray))
+        .scroll((app.preview_scroll, 0))
+        .block(Block::default().borders(Borders::ALL).title(title));
+    frame.render_widget(contents, columns[1]);
+}
+
+/// Show an error as its title followed by its message, which can be several lines for a failed command
+fn error_lines(error: &AppError) -> Vec<Line<'static>> {
+    let mut lines = vec![Line::from(Span::styled(error.title(), Style::default().fg(Color::LightRed)))];
+    lines.extend(error.to_string().lines().map(|line| Line::from(Span::styled(line.to_string(), Style::default().fg(Color::Red)))));
+    lines
+}
+
+/// The status lines shown at the top of the project created screen
+fn created_lines(app: &App) -> Vec<Line<'static>> {
+    let status = match &app.error {
+        Some(error) => error_lines(error),
+        None if app.dry_run => vec![Line::from(Span::styled("Dry run, nothing was written, the plan is printed when you quit", Style::default().fg(Color::Green)))],
+        None => vec![Line::from(Span::styled("Project created successfully", Style::default().fg(Color::Green)))],
+    };
+    let hint = if app.steps_running() {
+        Line::from(Span::styled("Running post create steps...", Style::default().fg(Color::Yellow)))
+    } else if app.error.is_none() && !app.dry_run {
+        Line::from(Span::styled("(o) open in editor / (i) open in IDE / (f) open folder / (s) enter project shell / any other key to continue", Style::default().fg(Color::White)))
+    } else {
+        Line::from(Span::styled("Press any key to continue", Style::default().fg(Color::White)))
+    };
+    let mut lines = status;
+    if let Some(message) = &app.message {
+        lines.push(Line::from(Span::styled(message.clone(), Style::default().fg(Color::Yellow))));
+    }
+    lines.push(hint);
+    lines
+}
+
+/// Render the prompts of a template that are asked, each with its error or help below it
+///
+/// Scrolls so the focused prompt is always on screen.
+fn render_form(frame: &mut Frame, form: &Form, area: Rect) {
+    let visible = form.visible();
+    let per_page = (area.height / 4).max(1) as usize;
+    let focused = visible.iter().position(|index| *index == form.focus).unwrap_or(0);
+    let first = focused.saturating_sub(per_page - 1);
+
+    let shown: Vec<usize> = visible.into_iter().skip(first).take(per_page).collect();
+    let mut constraints = vec![Constraint::Length(4); shown.len()];
+    constraints.push(Constraint::Min(0));
+    let rows = Layout::default().direction(Direction::Vertical).constraints(constraints).split(area);
+
+    for (index, row) in shown.into_iter().zip(rows.iter()) {
+        let prompt = &form.prompts[index];
+        let focused = index == form.focus;
+        let value = match prompt.kind {
+            PromptType::Bool => checkbox(form.value(index) == "true"),
+            PromptType::Choice => format!("< {} >", form.value(index)),
+            PromptType::String | PromptType::Integer => form.value(index).to_string(),
+        };
+        let field = Rect { height: 3, ..*row };
+        render_input_box(frame, field, prompt.label(), &value, focused && matches!(prompt.kind, PromptType::String | PromptType::Integer));
+        if focused && !matches!(prompt.kind, PromptType::String | PromptType::Integer) {
+            // Input boxes only show focus for text, so outline toggles and choices too
+            let outline = Block::default().borders(Borders::ALL).border_style(Style::default().fg(Color::LightBlue)).title(Span::styled(prompt.label(), Style::default().fg(Color::LightBlue)));
+            frame.render_widget(outline, field);
+        }
+
+        let note = match form.error(index) {
+            Some(error) => Some(Span::styled(error, Style::default().fg(Color::Red))),
+            None if focused && !prompt.help.is_empty() => Some(Span::styled(prompt.help.clone(), Style::default().fg(Color::Gray))),
+            None => None,
+        };
+        if let (Some(note), true) = (note, row.height > 3) {
+            frame.render_widget(Paragraph::new(Line::from(note)), Rect { y: row.y + 3, height: 1, ..*row });
+        }
+    }
+}
+
+/// Render the list of post create steps and the output of the latest one
+fn render_steps(frame: &mut Frame, app: &App, list_area: Rect, output_area: Rect) {
+    let lines: Vec<Line> = app
+        .steps
+        .iter()
+        .map(|state| {
+            let (marker, colour) = match &state.status {
+                StepStatus::Pending => ("[ ]".to_string(), Color::DarkGray),
+                StepStatus::Running => ("[~]".to_string(), Color::Yellow),
+                StepStatus::Succeeded => ("[✓]".to_string(), Color::Green),
+                StepStatus::Failed(error) => (format!("[✗] {}:", error), Color::Red),
+                StepStatus::Skipped => ("[-] skipped:".to_string(), Color::DarkGray),
+            };
+            let optional = if state.step.required { "" } else { " (optional)" };
+            Line::from(vec![
+                Span::styled(marker, Style::default().fg(colour)),
+                Span::styled(format!(" {}{}", state.step.command.join(" "), optional), Style::default().fg(Color::White)),
+            ])
+        })
+        .collect();
+    let list = Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title("Post Create Steps"));
+    frame.render_widget(list, list_area);
+
+    // Show the step that is running, or the last one that printed anything
+    let latest = app
+        .steps
+        .iter()
+        .rev()
+        .find(|state| state.status == StepStatus::Running || !state.output.is_empty());
+    let visible = output_area.height.saturating_sub(2) as usize;
+    let output: Vec<Line> = latest
+        .map(|state| {
+            let skip = state.output.len().saturating_sub(visible);
+            state.output[skip..].iter().map(|line| Line::from(line.clone())).collect()
+        })
+        .unwrap_or_default();
+    let para = Paragraph::new(output)
+        .style(Style::default().fg(Color::Gray))
+        .block(Block::default().borders(Borders::ALL).title("Output"));
+    frame.render_widget(para, output_area);
+}
+
+/// Render the progress of `create-project batch` as a table with one row per project
+pub fn render_batch(frame: &mut Frame, jobs: &[Job], cancelled: bool) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(3)])
+        .split(frame.area());
+
+    let rows = jobs.iter().map(|job| {
+        let (status, colour) = match &job.status {
+            JobStatus::Waiting => ("waiting".to_string(), Color::DarkGray),
+            JobStatus::Running => ("running".to_string(), Color::Yellow),
+            JobStatus::Succeeded => ("created".to_string(), Color::Green),
+            JobStatus::Failed(_) => ("failed".to_string(), Color::Red),
+            JobStatus::Cancelled => ("cancelled".to_string(), Color::DarkGray),
+        };
+        let detail = match &job.status {
+            JobStatus::Failed(error) => error.clone(),
+            _ => job.output.clone().unwrap_or_default(),
+        };
+        Row::new(vec![
+            Cell::from(job.name.clone()).style(Style::default().fg(Color::White)),
+            Cell::from(job.folder.clone()).style(Style::default().fg(Color::Gray)),
+            Cell::from(status).style(Style::default().fg(colour)),
+            Cell::from(detail).style(Style::default().fg(Color::Gray)),
+        ])
+    });
+
+    let done = jobs.iter().filter(|job| !matches!(job.status, JobStatus::Waiting | JobStatus::Running)).count();
+    let table = Table::new(rows, [Constraint::Percentage(20), Constraint::Percentage(25), Constraint::Length(10), Constraint::Min(10)])
+        .header(Row::new(vec!["Name", "Folder", "Status", "Output"]).style(Style::default().fg(Color::LightBlue)))
+        .block(Block::default().borders(Borders::ALL).title(format!("Creating projects ({}/{})", done, jobs.len())));
+    frame.render_widget(table, chunks[0]);
+
+    let hint = if cancelled {
+        Span::styled("Stopping, waiting for the running projects to finish", Style::default().fg(Color::Yellow))
+    } else {
+        Span::styled("(q) stop starting new projects", Style::default().fg(Color::LightBlue))
+    };
+    let footer = Paragraph::new(Line::from(hint)).alignment(Alignment::Center).block(Block::default().borders(Borders::ALL));
+    frame.render_widget(footer, chunks[1]);
+}
+
+/// Draw the conflicts of an update, with the selected one shown between the lines around it
+/// ### Parameters
+///  - frame: The frame to draw on
+///  - updates: The files being updated
+///  - conflicts: Every conflict, as found by `update::conflicts`
+///  - selected: The index of the selected conflict
+pub fn render_merge(frame: &mut Frame, updates: &[FileUpdate], conflicts: &[(usize, usize)], selected: usize) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(3)])
+        .split(frame.area());
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(30), Constraint::Min(10)])
+        .split(chunks[0]);
+
+    let list: Vec<Line> = conflicts
+        .iter()
+        .enumerate()
+        .map(|(index, location)| {
+            let (mark, colour) = match update::conflict(updates, *location).and_then(|conflict| conflict.take_theirs) {
+                Some(true) => ("template", Color::Green),
+                Some(false) => ("project ", Color::Red),
+                None => ("        ", Color::White),
+            };
+            let text = format!("{} {}:{}", mark, updates[location.0].path.display(), conflict_line(updates, *location));
+            let style = if index == selected { Style::default().fg(colour).bg(Color::Blue) } else { Style::default().fg(colour) };
+            Line::from(Span::styled(text, style))
+        })
+        .collect();
+    let decided = conflicts.iter().filter(|location| update::conflict(updates, **location).is_some_and(|conflict| conflict.take_theirs.is_some())).count();
+    let list = Paragraph::new(list).block(Block::default().borders(Borders::ALL).title(format!("Conflicts ({}/{} decided)", decided, conflicts.len())));
+    frame.render_widget(list, columns[0]);
+
+    if let Some(&(file, chunk)) = conflicts.get(selected)
+        && let FileChange::Merge(merged) = &updates[file].change
+        && let Some(Chunk::Conflict(conflict)) = merged.get(chunk)
+    {
+        let context = |chunk: Option<&Chunk>, before: bool| -> Vec<Line> {
+            let Some(Chunk::Resolved(lines)) = chunk else {
+                return Vec::new();
+            };
+            let shown = if before { &lines[lines.len().saturating_sub(3)..] } else { &lines[..lines.len().min(3)] };
+            shown.iter().map(|line| Line::from(Span::styled(format!("  {}", line.trim_end_matches('\n')), Style::default().fg(Color::DarkGray)))).collect()
+        };
+        let side = |lines: &[String], prefix: &str, colour: Color, chosen: bool| -> Vec<Line> {
+            let colour = if chosen { colour } else { Color::DarkGray };
+            lines.iter().map(|line| Line::from(Span::styled(format!("{} {}", prefix, line.trim_end_matches('\n')), Style::default().fg(colour)))).collect()
+        };
+
+        let mut lines = context(chunk.checked_sub(1).and_then(|index| merged.get(index)), true);
+        lines.push(Line::from(Span::styled("<<<<<<< project", Style::default().fg(Color::Gray))));
+        lines.extend(side(&conflict.ours, "-", Color::Red, conflict.take_theirs != Some(true)));
+        lines.push(Line::from(Span::styled("=======", Style::default().fg(Color::Gray))));
+        lines.extend(side(&conflict.theirs, "+", Color::Green, conflict.take_theirs != Some(false)));
+        lines.push(Line::from(Span::styled(">>>>>>> template", Style::default().fg(Color::Gray))));
+        lines.extend(context(merged.get(chunk + 1), false));
+
+        let title = format!("{} ({} of {})", updates[file].path.display(), selected + 1, conflicts.len());
+        frame.render_widget(Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(title)), columns[1]);
+    }
+
+    let hint = Span::styled(
+        "(↑/↓) conflict / (a) take template / (r) keep project / (enter) write / (q) cancel",
+        Style::default().fg(Color::LightBlue),
+    );
+    let footer = Paragraph::new(Line::from(hint)).alignment(Alignment::Center).block(Block::default().borders(Borders::ALL));
+    frame.render_widget(footer, chunks[1]);
+}
+
+/// Get the line of the project's file a conflict starts on
+fn conflict_line(updates: &[FileUpdate], (file, chunk): (usize, usize)) -> usize {
+    let FileChange::Merge(chunks) = &updates[file].change else {
+        return 1;
+    };
+    let before: usize = chunks[..chunk]
+        .iter()
+        .map(|chunk| match chunk {
+            Chunk::Resolved(lines) => lines.len(),
+            Chunk::Conflict(conflict) => conflict.ours.len(),
+        })
+        .sum();
+    before + 1
+}
+
 /// Safe centered rectangle that falls back if space is too small.
 /// If resulting middle slice is too small to hold a bordered widget, returns the original r.
 fn safe_centred_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
@@ -188,7 +669,7 @@ fn safe_centred_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
 fn render_input_box(frame: &mut Frame, area: Rect, title: &str, value: &str, focused: bool) {
     // Keep the external sizing rule you had (cap and floor height)
     let area = Rect {
-        height: area.height.min(5).max(3),
+        height: area.height.clamp(3, 5),
         ..area
     };
 
@@ -250,7 +731,7 @@ fn render_input_box(frame: &mut Frame, area: Rect, title: &str, value: &str, foc
     // (Optional) Show a cursor at end of text if focused:
     if focused {
         let cursor_x = text_area.x.saturating_add(shown.len() as u16);
-        frame.set_cursor(cursor_x.min(text_area.x + text_area.width.saturating_sub(1)), text_area.y);
+        frame.set_cursor_position((cursor_x.min(text_area.x + text_area.width.saturating_sub(1)), text_area.y));
     }
 }
 
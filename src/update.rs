@@ -0,0 +1,301 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::app::{App, ProjectTypes};
+use crate::error::AppError;
+use crate::merge::{self, Chunk, Conflict};
+use crate::plan::{self, Plan};
+use crate::recipe::Recipe;
+use crate::source::{self, TemplateSource};
+
+/// The file in every project recording how it was created
+pub const RECORD_FILE: &str = ".project-creation.toml";
+
+/// How a project was created, so it can be updated when its template changes
+///
+/// Unknown fields are ignored, so a project written by a newer create-project can still be updated by an older one.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ProjectRecord {
+    /// The version of create-project that last wrote the project
+    pub generator: String,
+    /// The `version` the template declares
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// The commit of the repository the template was fetched from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    /// The choices and answers the project was created with
+    pub recipe: Recipe,
+    /// A fingerprint of every file as the template wrote it, to tell which files the project has changed
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub files: BTreeMap<String, String>,
+}
+
+impl ProjectRecord {
+    /// Read the record of the project in a folder
+    pub fn load(dir: &Path) -> Result<ProjectRecord, AppError> {
+        let path = dir.join(RECORD_FILE);
+        let text = fs::read_to_string(&path).map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => {
+                AppError::Validation(format!("{} has no {}, it wasn't created by create-project", dir.display(), RECORD_FILE))
+            }
+            _ => AppError::Io(path.clone(), e),
+        })?;
+        toml::from_str(&text).map_err(|e| AppError::ConfigParse(path, e.message().to_string()))
+    }
+
+    /// Fingerprint the files of a plan, leaving out the record itself
+    pub fn fingerprint_files(plan: &Plan) -> BTreeMap<String, String> {
+        plan.files
+            .iter()
+            .filter(|file| file.path != Path::new(RECORD_FILE))
+            .map(|file| (file.path.to_string_lossy().replace('\\', "/"), fingerprint(&file.contents)))
+            .collect()
+    }
+
+    /// Get the record as the contents of its file
+    pub fn to_toml(&self) -> String {
+        let text = toml::to_string(self).unwrap_or_default();
+        format!("# Written by create-project, `create-project update` uses it to update the project from its template\n{}", text)
+    }
+}
+
+/// What updating does to one file
+#[derive(Clone, Debug)]
+pub enum FileChange {
+    /// Write the file, because it is new or only the template changed it
+    Write(String),
+    /// Remove the file, because the template no longer has it and the project didn't change it
+    Remove,
+    /// Merge the project's and the template's changes
+    Merge(Vec<Chunk>),
+}
+
+/// A file that updating changes
+#[derive(Clone, Debug)]
+pub struct FileUpdate {
+    /// The path of the file, relative to the project folder
+    pub path: PathBuf,
+    pub change: FileChange,
+    /// Whether the file is in the project now
+    pub existed: bool,
+    /// Whether the new version of the template has the file
+    pub in_template: bool,
+    /// Whether the template makes the file executable
+    pub executable: bool,
+}
+
+impl FileUpdate {
+    /// Get a word describing what happens to the file
+    pub fn label(&self) -> &'static str {
+        match &self.change {
+            FileChange::Write(_) if self.existed => "update",
+            FileChange::Write(_) => "add",
+            FileChange::Remove => "remove",
+            FileChange::Merge(chunks) if merge::has_conflicts(chunks) => "conflict",
+            FileChange::Merge(_) => "merge",
+        }
+    }
+}
+
+/// Work out how to update the project in a folder to the latest version of its template
+///
+/// The template is rendered again with the recorded answers. Files only the
+/// template changed are updated and files only the project changed are kept,
+/// going by the recorded fingerprints. When the template came from git it is
+/// also rendered as it was when the project was created, and that is used as
+/// the base of a three-way merge of files both have changed. Otherwise any
+/// difference in those files is a conflict.
+/// ### Returns
+///  - The files to change, and the new record to write
+pub fn plan_update(dir: &Path) -> Result<(Vec<FileUpdate>, ProjectRecord), AppError> {
+    let mut record = ProjectRecord::load(dir)?;
+    if record.commit.is_some()
+        && let Some(spec) = &record.recipe.template
+    {
+        record.recipe.template = Some(resolve_legacy_spec(dir, spec));
+    }
+    let mut app = App::new();
+    if let Some(error) = app.error.take() {
+        return Err(error);
+    }
+    let theirs = app.plan_recipe(&record.recipe)?;
+    let base = match (&record.commit, &record.recipe.template) {
+        (Some(commit), Some(spec)) => Some(base_plan(&record.recipe, spec, commit)?),
+        _ => None,
+    };
+
+    let mut paths: BTreeSet<PathBuf> = theirs.files.iter().map(|file| file.path.clone()).collect();
+    if let Some(base) = &base {
+        paths.extend(base.files.iter().map(|file| file.path.clone()));
+    }
+    paths.extend(record.files.keys().map(PathBuf::from));
+    paths.remove(Path::new(RECORD_FILE));
+    for path in &paths {
+        plan::check_path(path).map_err(|message| AppError::Validation(message).context(format!("{} names a file it can't update", RECORD_FILE)))?;
+    }
+
+    let mut updates = Vec::new();
+    for path in paths {
+        let new_file = theirs.files.iter().find(|file| file.path == path);
+        let new = new_file.map(|file| file.contents.as_str());
+        let current = fs::read_to_string(dir.join(&path)).ok();
+        let current = current.as_deref();
+        let old = base.as_ref().map(|base| base.files.iter().find(|file| file.path == path).map(|file| file.contents.as_str()));
+
+        // Whether each side differs from what the template first wrote
+        let (template_changed, project_changed) = match (old, record.files.get(&path.to_string_lossy().replace('\\', "/"))) {
+            (Some(old), _) => (new != old, current != old),
+            (None, Some(recorded)) => (new.map(fingerprint).as_ref() != Some(recorded), current.map(fingerprint).as_ref() != Some(recorded)),
+            // A record without fingerprints can't tell, so anything the two disagree on is a conflict
+            (None, None) if record.files.is_empty() => (true, current.is_some()),
+            (None, None) => (new.is_some(), current.is_some()),
+        };
+        let change = match (new, current) {
+            _ if new == current || !template_changed => continue,
+            (Some(new), _) if !project_changed => FileChange::Write(new.to_string()),
+            (None, Some(_)) if !project_changed => FileChange::Remove,
+            (None, None) => continue,
+            _ => {
+                let old = old.map(|old| old.unwrap_or(""));
+                FileChange::Merge(merge::merge(old, current.unwrap_or(""), new.unwrap_or("")))
+            }
+        };
+        updates.push(FileUpdate {
+            path,
+            change,
+            existed: current.is_some(),
+            in_template: new.is_some(),
+            executable: new_file.is_some_and(|file| file.executable),
+        });
+    }
+
+    let record = theirs
+        .files
+        .iter()
+        .find(|file| file.path == Path::new(RECORD_FILE))
+        .and_then(|file| toml::from_str(&file.contents).ok())
+        .unwrap_or(record);
+    Ok((updates, record))
+}
+
+/// Find the repository of a template recorded with a relative path, by older versions that didn't save the full path
+///
+/// The path was relative to where the project was created, which is the folder the
+/// project is in, so it is looked for there before the project folder itself. Anything
+/// else is returned as it is.
+fn resolve_legacy_spec(dir: &Path, spec: &str) -> String {
+    let Ok(source) = TemplateSource::parse(spec) else {
+        return spec.to_string();
+    };
+    if source.repo.is_absolute() {
+        return spec.to_string();
+    }
+    let Ok(dir) = dir.canonicalize() else {
+        return spec.to_string();
+    };
+    [dir.parent(), Some(dir.as_path())]
+        .into_iter()
+        .flatten()
+        .find_map(|base| base.join(&source.repo).canonicalize().ok())
+        .map(|repo| source.to_spec(&repo))
+        .unwrap_or_else(|| spec.to_string())
+}
+
+/// Render the template a project was created from as it was at the recorded commit
+fn base_plan(recipe: &Recipe, spec: &str, commit: &str) -> Result<Plan, AppError> {
+    source::with_commit(spec, commit, |template| {
+        let mut app = App::new();
+        app.apply_recipe(&Recipe { template: None, ..recipe.clone() })?;
+        // A recipe without a type or template is a Python project, so the old template is chosen after it
+        app.templates.push(template);
+        app.project_type = ProjectTypes::Template(app.templates.len() - 1);
+        app.plan_choices()
+    })
+    .map_err(|e| e.context("couldn't render the template as it was when the project was created"))
+}
+
+/// Get a fingerprint of a file's contents that stays the same between versions of create-project and of Rust
+pub fn fingerprint(contents: &str) -> String {
+    // 64 bit FNV-1a
+    let hash = contents.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3));
+    format!("{:016x}", hash)
+}
+
+/// Write the updates and the new record into the project
+///
+/// Conflicts that haven't been decided are written with conflict markers. Nothing is
+/// written if any of the paths would be outside the project.
+pub fn apply(dir: &Path, updates: &[FileUpdate], record: &ProjectRecord) -> Result<(), AppError> {
+    for update in updates {
+        plan::check_path(&update.path).map_err(AppError::Validation)?;
+    }
+    for update in updates {
+        let path = dir.join(&update.path);
+        let contents = match &update.change {
+            FileChange::Write(contents) => contents.clone(),
+            FileChange::Remove => {
+                fs::remove_file(&path).map_err(AppError::io(&path))?;
+                continue;
+            }
+            FileChange::Merge(chunks) => merge::join(chunks),
+        };
+
+        // Taking the side that doesn't have the file leaves nothing to write
+        if contents.is_empty() && !(update.existed && update.in_template) {
+            if update.existed {
+                fs::remove_file(&path).map_err(AppError::io(&path))?;
+            }
+            continue;
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(AppError::io(parent))?;
+        }
+        fs::write(&path, contents).map_err(AppError::io(&path))?;
+        #[cfg(unix)]
+        if update.executable && !update.existed {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).map_err(AppError::io(&path))?;
+        }
+    }
+    let path = dir.join(RECORD_FILE);
+    fs::write(&path, record.to_toml()).map_err(AppError::io(&path))
+}
+
+/// Find every conflict in the updates, by the index of the file and of the chunk in its merge
+pub fn conflicts(updates: &[FileUpdate]) -> Vec<(usize, usize)> {
+    let mut found = Vec::new();
+    for (file, update) in updates.iter().enumerate() {
+        if let FileChange::Merge(chunks) = &update.change {
+            found.extend(chunks.iter().enumerate().filter(|(_, chunk)| matches!(chunk, Chunk::Conflict(_))).map(|(index, _)| (file, index)));
+        }
+    }
+    found
+}
+
+/// Get a conflict found by `conflicts`
+pub fn conflict(updates: &[FileUpdate], (file, chunk): (usize, usize)) -> Option<&Conflict> {
+    match &updates.get(file)?.change {
+        FileChange::Merge(chunks) => match chunks.get(chunk)? {
+            Chunk::Conflict(conflict) => Some(conflict),
+            Chunk::Resolved(_) => None,
+        },
+        _ => None,
+    }
+}
+
+/// Get a conflict found by `conflicts` to decide it
+pub fn conflict_mut(updates: &mut [FileUpdate], (file, chunk): (usize, usize)) -> Option<&mut Conflict> {
+    match &mut updates.get_mut(file)?.change {
+        FileChange::Merge(chunks) => match chunks.get_mut(chunk)? {
+            Chunk::Conflict(conflict) => Some(conflict),
+            Chunk::Resolved(_) => None,
+        },
+        _ => None,
+    }
+}